@@ -0,0 +1,276 @@
+//! The days of a lifespan and what each of them is: holidays, highlights, shifts and how they are coloured.
+
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeMap;
+
+use super::color::Color;
+use super::config::Config;
+use super::error::LyfcalError;
+use super::schedule::shift_state;
+
+/// Every day of the configured lifespan with the holidays and highlights falling on them.
+#[derive(Default, Debug, Clone)]
+pub struct Calendar {
+    pub config: Config,
+    pub events: BTreeMap<NaiveDate, String>,
+    pub holidays: BTreeMap<NaiveDate, &'static str>,
+    /// Index into config.highlights of the first highlight matching each day.
+    pub highlighted_days: BTreeMap<NaiveDate, usize>,
+    //Elapsed date shown instead of the configured one while time travelling.
+    preview_date: Option<NaiveDate>,
+}
+
+/// How much of the drawing state a config edit invalidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigChange {
+    None,
+    Repaint,
+    Relayout,
+    Rebuild,
+}
+
+impl Calendar {
+    /// Calendar populated for the given config, which must be valid.
+    pub fn new(config: &Config) -> Result<Self, LyfcalError> {
+        let mut calendar = Self::default();
+        calendar.update_config(config)?;
+        Ok(calendar)
+    }
+
+    /// Take on a new config, recomputing the days only when the change requires it.
+    /// An invalid config is rejected and the calendar kept as it was.
+    pub fn update_config(&mut self, config: &Config) -> Result<ConfigChange, LyfcalError> {
+        config.validate()?;
+        let change = if self.events.is_empty() || self.config.days_differ(config) {
+            ConfigChange::Rebuild
+        } else if self.config.layout_differs(config) {
+            ConfigChange::Relayout
+        } else if self.config.drawing_differs(config) {
+            ConfigChange::Repaint
+        } else {
+            ConfigChange::None
+        };
+        if self.config != *config {
+            self.config = config.clone();
+        }
+        if change == ConfigChange::Rebuild {
+            self.populate_events()?;
+        }
+        Ok(change)
+    }
+
+    /// Move the elapsed date without rebuilding events.
+    pub fn set_elapsed_date(&mut self, date: NaiveDate) {
+        self.config.elapsed_date = date;
+    }
+
+    pub fn set_preview_date(&mut self, date: Option<NaiveDate>) {
+        self.preview_date = date;
+    }
+
+    /// The preview date while time travelling, otherwise the configured elapsed date.
+    pub fn elapsed_date(&self) -> NaiveDate {
+        self.preview_date.unwrap_or(self.config.elapsed_date)
+    }
+
+    /// Populate events with every day from the birthdate up to the end of the life expectancy.
+    pub fn populate_events(&mut self) -> Result<(), LyfcalError> {
+        let (birthdate, end) = self.config.lifespan()?;
+        self.events.clear();
+        self.holidays.clear();
+        self.highlighted_days.clear();
+
+        //The end, the birthday at the life expectancy, is included.
+        for date in birthdate.iter_days().take_while(|date| *date <= end) {
+            self.events.insert(date, format!("{}", date.weekday()));
+        }
+
+        if let Some(region) = self.config.holiday_region {
+            self.holidays = region.holidays_between(birthdate, end);
+        }
+
+        for (index, highlight) in self.config.highlights.iter().enumerate() {
+            for date in highlight.rule.occurrences(highlight.start, end) {
+                if date >= birthdate {
+                    self.highlighted_days.entry(date).or_insert(index);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Count of (weekdays, weekend days) still ahead of the elapsed date.
+    pub fn remaining_day_counts(&self) -> (usize, usize) {
+        self.events
+            .keys()
+            .filter(|date| **date > self.elapsed_date())
+            .fold((0, 0), |(weekdays, weekends), date| {
+                if self.config.is_weekend(date.weekday()) {
+                    (weekdays, weekends + 1)
+                } else {
+                    (weekdays + 1, weekends)
+                }
+            })
+    }
+
+    /// On-shift days after the elapsed date up to and including the day before retirement.
+    pub fn working_days_remaining(&self) -> usize {
+        let Some(retirement_date) = self.config.birthday_at_age(self.config.retirement_age) else {
+            return 0;
+        };
+        self.events
+            .keys()
+            .filter(|date| **date > self.elapsed_date() && **date < retirement_date)
+            .filter(|date| shift_state(&self.config.work_schedules, **date) == Some(true))
+            .count()
+    }
+
+    /// Number of weekday slots left empty before the birthdate in the first week.
+    pub fn first_week_offset(&self) -> usize {
+        self.events.first_key_value().map_or(0, |(birthdate, _)| {
+            self.config.weekday_index(birthdate.weekday())
+        })
+    }
+
+    /// Number of week strips the lifespan spans.
+    pub fn week_count(&self) -> usize {
+        (self.events.len() + self.first_week_offset()).div_ceil(7)
+    }
+
+    /// Fill colours of a day's unit from the bottom layer up: the date type, then any highlight and the birthday.
+    pub fn unit_fills(&self, date: NaiveDate) -> impl Iterator<Item = Color> {
+        // Determine the date type, shift schedules take precedence over holidays, weekdays and weekends.
+        let date_type = if let Some(on_shift) = shift_state(&self.config.work_schedules, date) {
+            if on_shift {
+                DateType::OnShift
+            } else {
+                DateType::OffShift
+            }
+        } else if self.holidays.contains_key(&date) {
+            DateType::Holiday
+        } else if !self.config.is_weekend(date.weekday()) {
+            DateType::Weekday
+        } else {
+            DateType::Weekend
+        };
+
+        // Determine if the date is elapsed or in the future.
+        let is_elapsed = date <= self.elapsed_date();
+
+        // Use a match statement to determine the color based on the tuple of (is_elapsed, date_type).
+        let color = match (is_elapsed, date_type) {
+            (false, DateType::Weekday) => self.config.color_weekday,
+            (true, DateType::Weekday) => self.config.color_weekday_elapsed,
+            (false, DateType::Weekend) => self.config.color_weekend,
+            (true, DateType::Weekend) => self.config.color_weekend_elapsed,
+            (false, DateType::Holiday) => self.config.color_holiday,
+            (true, DateType::Holiday) => self.config.color_holiday_elapsed,
+            (false, DateType::OnShift) => self.config.color_on_shift,
+            (true, DateType::OnShift) => self.config.color_on_shift_elapsed,
+            (false, DateType::OffShift) => self.config.color_off_shift,
+            (true, DateType::OffShift) => self.config.color_off_shift_elapsed,
+        };
+
+        let highlight = self
+            .highlighted_days
+            .get(&date)
+            .and_then(|index| self.config.highlights.get(*index))
+            .map(|highlight| {
+                if is_elapsed {
+                    highlight.color_elapsed
+                } else {
+                    highlight.color
+                }
+            });
+
+        let birthday = self.config.is_birthday(date).then_some(if is_elapsed {
+            self.config.color_birthday_elapsed
+        } else {
+            self.config.color_birthday
+        });
+
+        std::iter::once(color).chain(highlight).chain(birthday)
+    }
+}
+
+enum DateType {
+    Weekday,
+    Weekend,
+    Holiday,
+    OnShift,
+    OffShift,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LeapDayPolicy;
+    use crate::schedule::{parse_pattern, WorkSchedule};
+    use crate::testing;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn leap_day_lifespan_is_drawn_with_birthdays_under_policy() {
+        for (policy, last_day) in [
+            (LeapDayPolicy::February28, date(2100, 2, 28)),
+            (LeapDayPolicy::March1, date(2100, 3, 1)),
+        ] {
+            let config = Config {
+                birthdate: Some(date(2000, 2, 29)),
+                leap_day_policy: policy,
+                life_expectancy: 100,
+                ..testing::config()
+            };
+            let calendar = Calendar::new(&config).unwrap();
+            assert_eq!(calendar.events.keys().next_back(), Some(&last_day));
+            assert_eq!(
+                calendar.events.len() as i64,
+                (last_day - date(2000, 2, 29)).num_days() + 1
+            );
+
+            let birthdays = calendar
+                .events
+                .keys()
+                .filter(|date| calendar.unit_fills(**date).count() > 1)
+                .count();
+            //The birthdate and one birthday for every year of age, including the last day.
+            assert_eq!(birthdays, 101);
+            assert_eq!(calendar.unit_fills(last_day).count(), 2);
+        }
+    }
+
+    #[test]
+    fn working_days_remaining_counts_shifts_until_retirement() {
+        let config = Config {
+            birthdate: Some(date(2000, 1, 1)),
+            life_expectancy: 80,
+            retirement_age: 67,
+            elapsed_date_bool: false,
+            elapsed_date: date(2066, 12, 1),
+            ..testing::config()
+        };
+        let calendar = |work_schedules| {
+            Calendar::new(&Config {
+                work_schedules,
+                ..config.clone()
+            })
+            .unwrap()
+        };
+        let schedule = |until| WorkSchedule {
+            name: "5 on / 2 off".to_string(),
+            pattern: parse_pattern("1111100").unwrap(),
+            anchor: date(2066, 11, 30),
+            until,
+        };
+        assert_eq!(calendar(Vec::new()).working_days_remaining(), 0);
+        //The 2nd to the 31st of December, the elapsed date and retirement day itself left out.
+        assert_eq!(calendar(vec![schedule(None)]).working_days_remaining(), 22);
+        assert_eq!(
+            calendar(vec![schedule(Some(date(2066, 12, 15)))]).working_days_remaining(),
+            10
+        );
+    }
+}
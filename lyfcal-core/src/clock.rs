@@ -0,0 +1,252 @@
+use chrono::{DateTime, Local, NaiveDate, TimeDelta, TimeZone};
+use std::time::Duration;
+
+//Longest wait between clock checks. Monotonic timers can stall while the machine is suspended,
+//so the wall clock is re-read at least this often to catch a missed midnight after resume.
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Source of the current local time, injected so previews and tests don't depend on the wall clock.
+pub trait Clock: std::fmt::Debug {
+    fn now(&self) -> DateTime<Local>;
+
+    fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// Clock stopped at a single instant, for tests and benchmarks.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Local>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Local> {
+        self.0
+    }
+}
+
+/// Keeps track of the local date and notices when it moves on.
+#[derive(Debug)]
+pub struct DateClock {
+    source: Box<dyn Clock>,
+    today: NaiveDate,
+}
+
+impl Default for DateClock {
+    fn default() -> Self {
+        Self::new(Box::new(SystemClock))
+    }
+}
+
+impl DateClock {
+    pub fn new(source: Box<dyn Clock>) -> Self {
+        Self {
+            today: source.today(),
+            source,
+        }
+    }
+
+    pub fn source(&self) -> &dyn Clock {
+        self.source.as_ref()
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.today
+    }
+
+    /// Re-read the wall clock, returning the new date if it changed since the last poll.
+    /// Midnight, DST changes, manual clock changes and resuming from suspend are all handled the same way.
+    pub fn poll(&mut self) -> Option<NaiveDate> {
+        let today = self.source.today();
+        if today != self.today {
+            self.today = today;
+            Some(today)
+        } else {
+            None
+        }
+    }
+
+    /// How long to wait before the next poll could see a new date.
+    pub fn time_until_rollover(&self) -> Duration {
+        until_rollover(&self.source.now())
+    }
+}
+
+//Time to the next local midnight, capped so the wall clock is still checked regularly.
+fn until_rollover<Tz: TimeZone>(now: &DateTime<Tz>) -> Duration {
+    next_midnight(now)
+        .and_then(|midnight| midnight.signed_duration_since(now).to_std().ok())
+        .unwrap_or(MAX_CHECK_INTERVAL)
+        .min(MAX_CHECK_INTERVAL)
+}
+
+//First instant of the next local day. Where DST skips midnight the day starts at the first valid time after it.
+fn next_midnight<Tz: TimeZone>(now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let midnight = now.date_naive().succ_opt()?.and_hms_opt(0, 0, 0)?;
+    (0..=180).find_map(|minutes| {
+        (midnight + TimeDelta::minutes(minutes))
+            .and_local_timezone(now.timezone())
+            .earliest()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, LocalResult, NaiveDateTime, NaiveTime};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    //Clock the test moves by hand, shared with the DateClock reading it.
+    #[derive(Debug, Clone)]
+    struct SettableClock(Rc<Cell<DateTime<Local>>>);
+
+    impl SettableClock {
+        fn set(&self, year: i32, month: u32, day: u32, hour: u32, min: u32, sec: u32) {
+            let now = Local.with_ymd_and_hms(year, month, day, hour, min, sec);
+            self.0.set(now.unwrap());
+        }
+    }
+
+    impl Clock for SettableClock {
+        fn now(&self) -> DateTime<Local> {
+            self.0.get()
+        }
+    }
+
+    //UTC in winter and an hour ahead in summer, switching at local midnight so DST moves the day's start:
+    //the 31st of March 2024 starts at 01:00 and lasts 23 hours, the 26th of October 2024 lasts 25 hours.
+    #[derive(Debug, Clone, Copy)]
+    struct MidnightDst;
+
+    impl MidnightDst {
+        fn summer(utc: &NaiveDateTime) -> bool {
+            let spring = NaiveDate::from_ymd_opt(2024, 3, 31)
+                .unwrap()
+                .and_time(NaiveTime::MIN);
+            let autumn = NaiveDate::from_ymd_opt(2024, 10, 26)
+                .unwrap()
+                .and_hms_opt(23, 0, 0)
+                .unwrap();
+            (spring..autumn).contains(utc)
+        }
+    }
+
+    impl TimeZone for MidnightDst {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Self
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            //Summer time first, as it is the earlier instant of an ambiguous time.
+            let offsets: Vec<FixedOffset> = [3600, 0]
+                .into_iter()
+                .map(|seconds| FixedOffset::east_opt(seconds).unwrap())
+                .filter(|offset| self.offset_from_utc_datetime(&(*local - *offset)) == *offset)
+                .collect();
+            match offsets[..] {
+                [offset] => LocalResult::Single(offset),
+                [earliest, latest] => LocalResult::Ambiguous(earliest, latest),
+                _ => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            FixedOffset::east_opt(if Self::summer(utc) { 3600 } else { 0 }).unwrap()
+        }
+    }
+
+    fn dst(month: u32, day: u32, hour: u32, min: u32) -> LocalResult<DateTime<MidnightDst>> {
+        MidnightDst.with_ymd_and_hms(2024, month, day, hour, min, 0)
+    }
+
+    #[test]
+    fn poll_reports_each_new_date_once() {
+        let clock = SettableClock(Rc::new(Cell::new(Local::now())));
+        clock.set(2024, 6, 1, 23, 59, 59);
+        let mut date_clock = DateClock::new(Box::new(clock.clone()));
+        assert_eq!(date_clock.poll(), None);
+
+        clock.set(2024, 6, 2, 0, 0, 0);
+        assert_eq!(date_clock.poll(), NaiveDate::from_ymd_opt(2024, 6, 2));
+        assert_eq!(
+            date_clock.today(),
+            NaiveDate::from_ymd_opt(2024, 6, 2).unwrap()
+        );
+        assert_eq!(date_clock.poll(), None);
+        clock.set(2024, 6, 2, 0, 0, 1);
+        assert_eq!(date_clock.poll(), None);
+
+        //Setting the clock back is a new date too.
+        clock.set(2024, 6, 1, 12, 0, 0);
+        assert_eq!(date_clock.poll(), NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert_eq!(date_clock.poll(), None);
+    }
+
+    #[test]
+    fn rollover_is_waited_for_in_capped_steps() {
+        let clock = SettableClock(Rc::new(Cell::new(Local::now())));
+        clock.set(2024, 6, 1, 23, 55, 0);
+        let date_clock = DateClock::new(Box::new(clock.clone()));
+        assert_eq!(
+            date_clock.time_until_rollover(),
+            Duration::from_secs(5 * 60)
+        );
+        clock.set(2024, 6, 1, 12, 0, 0);
+        assert_eq!(date_clock.time_until_rollover(), MAX_CHECK_INTERVAL);
+    }
+
+    #[test]
+    fn day_losing_an_hour_to_dst_lasts_23_hours() {
+        //Midnight is skipped, the day starting at the first valid minute after it.
+        let start = dst(3, 31, 1, 0).unwrap();
+        assert_eq!(dst(3, 31, 0, 0), LocalResult::None);
+        assert_eq!(next_midnight(&dst(3, 30, 23, 0).unwrap()), Some(start));
+        assert_eq!(next_midnight(&start).unwrap() - start, TimeDelta::hours(23));
+
+        assert_eq!(
+            until_rollover(&dst(3, 30, 23, 55).unwrap()),
+            Duration::from_secs(5 * 60)
+        );
+        assert_eq!(
+            until_rollover(&dst(3, 31, 23, 55).unwrap()),
+            Duration::from_secs(5 * 60)
+        );
+    }
+
+    #[test]
+    fn day_gaining_an_hour_to_dst_lasts_25_hours() {
+        let start = dst(10, 26, 0, 0).unwrap();
+        let end = dst(10, 27, 0, 0).unwrap();
+        assert_eq!(next_midnight(&start), Some(end));
+        assert_eq!(end - start, TimeDelta::hours(25));
+
+        //23:55 comes round twice, the first time the rollover is still an hour and five minutes away.
+        let first = dst(10, 26, 23, 55).earliest().unwrap();
+        let second = dst(10, 26, 23, 55).latest().unwrap();
+        assert_eq!(
+            next_midnight(&first).unwrap() - first,
+            TimeDelta::minutes(65)
+        );
+        assert_eq!(until_rollover(&first), MAX_CHECK_INTERVAL);
+        assert_eq!(until_rollover(&second), Duration::from_secs(5 * 60));
+    }
+}
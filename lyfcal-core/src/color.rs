@@ -0,0 +1,84 @@
+//! Colours shared by every renderer, independent of any UI toolkit.
+
+use serde::{Deserialize, Serialize};
+
+/// An sRGBA colour with premultiplied alpha, laid out and serialized like egui's `Color32`
+/// so front ends can convert it losslessly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Color(pub [u8; 4]);
+
+impl Color {
+    pub const TRANSPARENT: Self = Self([0, 0, 0, 0]);
+    pub const WHITE: Self = Self([255, 255, 255, 255]);
+
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self([r, g, b, 255])
+    }
+
+    pub const fn from_rgba_premultiplied(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self([r, g, b, a])
+    }
+
+    /// Premultiply in linear space, giving the same result as egui's `Color32::from_rgba_unmultiplied`.
+    pub fn from_rgba_unmultiplied(r: u8, g: u8, b: u8, a: u8) -> Self {
+        match a {
+            255 => Self::from_rgb(r, g, b),
+            0 => Self::TRANSPARENT,
+            _ => {
+                let alpha = a as f32 / 255.0;
+                let premultiply = |channel| gamma_from_linear(linear_from_gamma(channel) * alpha);
+                Self([premultiply(r), premultiply(g), premultiply(b), a])
+            }
+        }
+    }
+
+    pub const fn r(&self) -> u8 {
+        self.0[0]
+    }
+
+    pub const fn g(&self) -> u8 {
+        self.0[1]
+    }
+
+    pub const fn b(&self) -> u8 {
+        self.0[2]
+    }
+
+    pub const fn a(&self) -> u8 {
+        self.0[3]
+    }
+
+    pub const fn to_array(&self) -> [u8; 4] {
+        self.0
+    }
+
+    /// This colour composed over another in gamma space, the way egui's renderers blend.
+    pub fn over(&self, destination: Self) -> Self {
+        let inverse_alpha = 255 - self.a() as u32;
+        Self(std::array::from_fn(|i| {
+            (self.0[i] as u32 + (destination.0[i] as u32 * inverse_alpha + 127) / 255).min(255)
+                as u8
+        }))
+    }
+}
+
+//sRGB transfer functions, as used by egui.
+pub(crate) fn linear_from_gamma(channel: u8) -> f32 {
+    if channel <= 10 {
+        channel as f32 / 3294.6
+    } else {
+        ((channel as f32 + 14.025) / 269.025).powf(2.4)
+    }
+}
+
+pub(crate) fn gamma_from_linear(linear: f32) -> u8 {
+    if linear <= 0.0 {
+        0
+    } else if linear <= 0.0031308 {
+        (3294.6 * linear + 0.5) as u8
+    } else if linear <= 1.0 {
+        (269.025 * linear.powf(1.0 / 2.4) - 14.025 + 0.5) as u8
+    } else {
+        255
+    }
+}
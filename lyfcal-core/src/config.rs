@@ -0,0 +1,722 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use super::clock::{Clock, SystemClock};
+use super::color::Color;
+use super::error::{LyfcalError, MAX_AGE};
+use super::geometry::{Pos2, Vec2};
+use super::holiday::HolidayRegion;
+use super::recurrence::RecurrenceRule;
+use super::schedule::WorkSchedule;
+use super::theme::{Theme, WindowVisuals};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub birthdate: Option<NaiveDate>,
+    /// Day a 29 February birthday falls on in common years.
+    pub leap_day_policy: LeapDayPolicy,
+    pub elapsed_date_bool: bool,
+    pub elapsed_date: chrono::NaiveDate,
+    pub life_expectancy: i32,
+    pub week_start: Weekday,
+    pub weekend_days: Vec<Weekday>,
+    pub holiday_region: Option<HolidayRegion>,
+    pub highlights: Vec<Highlight>,
+    pub work_schedules: Vec<WorkSchedule>,
+    pub retirement_age: i32,
+    /// Free text attached to single days, marked on the grid.
+    pub notes: BTreeMap<NaiveDate, String>,
+
+    //pub display_weekends: bool,
+    //pub display_birthday: bool,
+    pub enable_transparency: bool,
+    pub enable_mouse_passthrough: bool,
+    pub window_layer: WindowLayer,
+    /// Last life viewport position and size, keyed by the monitor it was on.
+    #[serde(
+        serialize_with = "serialize_entries",
+        deserialize_with = "deserialize_window_geometry"
+    )]
+    pub window_geometry: BTreeMap<Monitor, WindowGeometry>,
+    /// Regenerate the wallpaper image and run the wallpaper command at every daily rollover.
+    pub enable_wallpaper: bool,
+    pub wallpaper_path: String,
+    /// Image drawn behind the grid, a plain background if empty.
+    pub wallpaper_background: String,
+    /// Shell command run on the written wallpaper, e.g. feh --bg-fill.
+    pub wallpaper_command: String,
+    pub color_weekday: Color,
+    pub color_weekday_elapsed: Color,
+    pub color_weekend: Color,
+    pub color_weekend_elapsed: Color,
+    pub color_holiday: Color,
+    pub color_holiday_elapsed: Color,
+    pub color_on_shift: Color,
+    pub color_on_shift_elapsed: Color,
+    pub color_off_shift: Color,
+    pub color_off_shift_elapsed: Color,
+    pub color_birthday: Color,
+    pub color_birthday_elapsed: Color,
+    pub color_today: Color,
+    /// Look of the config window, set along with the colours by a theme.
+    pub window_visuals: WindowVisuals,
+    pub unit_ratio: f32,
+    pub col_spacing: f32,
+    pub row_spacing: f32,
+    pub border_spacing: f32,
+    pub week_orientation: WeekOrientation,
+    pub horizontal_flow: HorizontalFlow,
+    pub vertical_flow: VerticalFlow,
+    pub flow_mode: FlowMode,
+    /// What the layout solver holds fixed while fitting the grid to the area.
+    pub grid_sizing: GridSizing,
+    /// Unit size in points, used with `GridSizing::FixedUnitSize`.
+    pub unit_size: f32,
+    /// Columns of week strips, used with `GridSizing::FixedColumns`.
+    pub column_count: usize,
+    /// Placement of the grid within the space it leaves over.
+    pub grid_alignment: GridAlignment,
+    pub live_preview: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new(&SystemClock)
+    }
+}
+
+impl Config {
+    /// Default config with the elapsed date taken from the given clock.
+    pub fn new(clock: &dyn Clock) -> Self {
+        let theme = Theme::dark();
+        Self {
+            birthdate: NaiveDate::from_ymd_opt(2000, 1, 1),
+            leap_day_policy: LeapDayPolicy::February28,
+            elapsed_date_bool: true,
+            elapsed_date: clock.today(),
+            life_expectancy: 80,
+            week_start: Weekday::Mon,
+            weekend_days: vec![Weekday::Sat, Weekday::Sun],
+            holiday_region: None,
+            highlights: Vec::new(),
+            work_schedules: Vec::new(),
+            retirement_age: 67,
+            notes: BTreeMap::new(),
+
+            //display_weekends: true,
+            //display_birthday: true,
+            enable_transparency: false,
+            enable_mouse_passthrough: false,
+            window_layer: WindowLayer::Normal,
+            window_geometry: BTreeMap::new(),
+            enable_wallpaper: false,
+            wallpaper_path: std::env::temp_dir()
+                .join("lyfcal-wallpaper.png")
+                .to_string_lossy()
+                .into_owned(),
+            wallpaper_background: String::new(),
+            wallpaper_command: String::new(),
+            color_weekday: theme.palette.weekday,
+            color_weekday_elapsed: theme.palette.weekday_elapsed,
+            color_weekend: theme.palette.weekend,
+            color_weekend_elapsed: theme.palette.weekend_elapsed,
+            color_holiday: theme.palette.holiday,
+            color_holiday_elapsed: theme.palette.holiday_elapsed,
+            color_on_shift: theme.palette.on_shift,
+            color_on_shift_elapsed: theme.palette.on_shift_elapsed,
+            color_off_shift: theme.palette.off_shift,
+            color_off_shift_elapsed: theme.palette.off_shift_elapsed,
+            color_birthday: theme.palette.birthday,
+            color_birthday_elapsed: theme.palette.birthday_elapsed,
+            color_today: theme.palette.today,
+            window_visuals: theme.window,
+            unit_ratio: 0.8,
+            col_spacing: 1.0,
+            row_spacing: 0.0,
+            border_spacing: 1.0,
+            week_orientation: WeekOrientation::Horizontal,
+            horizontal_flow: HorizontalFlow::LeftToRight,
+            vertical_flow: VerticalFlow::TopDown,
+            flow_mode: FlowMode::Linear,
+            grid_sizing: GridSizing::Fit,
+            unit_size: 12.0,
+            column_count: 4,
+            grid_alignment: GridAlignment::Center,
+            live_preview: true,
+        }
+    }
+
+    /// Whether the range of days, or the holidays and highlights precomputed for them, differ.
+    pub fn days_differ(&self, other: &Self) -> bool {
+        self.birthdate != other.birthdate
+            || self.leap_day_policy != other.leap_day_policy
+            || self.life_expectancy != other.life_expectancy
+            || self.holiday_region != other.holiday_region
+            || self.highlights.len() != other.highlights.len()
+            || self
+                .highlights
+                .iter()
+                .zip(&other.highlights)
+                .any(|(a, b)| a.start != b.start || a.rule != b.rule)
+    }
+
+    /// Whether the position or size of any unit differs.
+    pub fn layout_differs(&self, other: &Self) -> bool {
+        self.week_start != other.week_start
+            || self.unit_ratio != other.unit_ratio
+            || self.col_spacing != other.col_spacing
+            || self.row_spacing != other.row_spacing
+            || self.border_spacing != other.border_spacing
+            || self.week_orientation != other.week_orientation
+            || self.horizontal_flow != other.horizontal_flow
+            || self.vertical_flow != other.vertical_flow
+            || self.flow_mode != other.flow_mode
+            || self.grid_sizing != other.grid_sizing
+            || self.unit_size != other.unit_size
+            || self.column_count != other.column_count
+            || self.grid_alignment != other.grid_alignment
+    }
+
+    /// Whether anything drawn in the grid differs, window and wallpaper options don't count.
+    pub fn drawing_differs(&self, other: &Self) -> bool {
+        let other = Self {
+            enable_transparency: self.enable_transparency,
+            enable_mouse_passthrough: self.enable_mouse_passthrough,
+            window_layer: self.window_layer,
+            window_geometry: self.window_geometry.clone(),
+            enable_wallpaper: self.enable_wallpaper,
+            wallpaper_path: self.wallpaper_path.clone(),
+            wallpaper_background: self.wallpaper_background.clone(),
+            wallpaper_command: self.wallpaper_command.clone(),
+            window_visuals: self.window_visuals,
+            ..other.clone()
+        };
+        *self != other
+    }
+
+    pub fn is_weekend(&self, weekday: Weekday) -> bool {
+        self.weekend_days.contains(&weekday)
+    }
+
+    /// Position of the weekday within a week beginning on the configured week start.
+    pub fn weekday_index(&self, weekday: Weekday) -> usize {
+        weekday.days_since(self.week_start) as usize
+    }
+
+    /// All seven weekdays ordered from the configured week start.
+    pub fn week_days(&self) -> impl Iterator<Item = Weekday> {
+        std::iter::successors(Some(self.week_start), |day| Some(day.succ())).take(7)
+    }
+}
+
+//================================================== VALIDATION ==================================================
+
+pub const UNIT_RATIO_RANGE: RangeInclusive<f32> = 0.1..=1.0;
+pub const SPACING_RANGE: RangeInclusive<f32> = 0.0..=10.0;
+pub const BORDER_SPACING_RANGE: RangeInclusive<f32> = 0.0..=20.0;
+pub const UNIT_SIZE_RANGE: RangeInclusive<f32> = 1.0..=200.0;
+pub const COLUMN_COUNT_RANGE: RangeInclusive<f32> = 1.0..=520.0;
+
+impl Config {
+    /// Every reason the config can't be turned into a calendar, empty when it is valid.
+    pub fn problems(&self) -> Vec<LyfcalError> {
+        let mut problems = Vec::new();
+        if !(1..=MAX_AGE).contains(&self.life_expectancy) {
+            problems.push(LyfcalError::LifeExpectancy(self.life_expectancy));
+        } else if let Err(error) = self.lifespan() {
+            problems.push(error);
+        }
+        if !(1..=MAX_AGE).contains(&self.retirement_age) {
+            problems.push(LyfcalError::RetirementAge(self.retirement_age));
+        }
+        let layout_values = [
+            ("unit ratio", self.unit_ratio, UNIT_RATIO_RANGE),
+            ("column spacing", self.col_spacing, SPACING_RANGE),
+            ("row spacing", self.row_spacing, SPACING_RANGE),
+            ("border spacing", self.border_spacing, BORDER_SPACING_RANGE),
+            ("unit size", self.unit_size, UNIT_SIZE_RANGE),
+            ("column count", self.column_count as f32, COLUMN_COUNT_RANGE),
+        ];
+        for (setting, value, range) in layout_values {
+            if !range.contains(&value) {
+                problems.push(LyfcalError::OutOfRange {
+                    setting,
+                    value,
+                    range,
+                });
+            }
+        }
+        problems
+    }
+
+    /// The first problem of the config, if any.
+    pub fn validate(&self) -> Result<(), LyfcalError> {
+        match self.problems().into_iter().next() {
+            Some(problem) => Err(problem),
+            None => Ok(()),
+        }
+    }
+
+    /// First and last day of the lifespan, the birthdate and the birthday at the life expectancy.
+    pub fn lifespan(&self) -> Result<(NaiveDate, NaiveDate), LyfcalError> {
+        let birthdate = self.birthdate.ok_or(LyfcalError::MissingBirthdate)?;
+        let end =
+            self.birthday_at_age(self.life_expectancy)
+                .ok_or(LyfcalError::LifespanOutOfRange {
+                    birthdate,
+                    life_expectancy: self.life_expectancy,
+                })?;
+        Ok((birthdate, end))
+    }
+}
+
+//================================================== BIRTHDAYS ==================================================
+
+/// Day a 29 February birthday is celebrated on when the year has no 29 February.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LeapDayPolicy {
+    February28,
+    March1,
+}
+
+impl LeapDayPolicy {
+    pub const ALL: [Self; 2] = [Self::February28, Self::March1];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::February28 => "28 february",
+            Self::March1 => "1 march",
+        }
+    }
+}
+
+impl Config {
+    /// Birthday within the given year, following the leap day policy in common years.
+    pub fn birthday_in(&self, year: i32) -> Option<NaiveDate> {
+        let birthdate = self.birthdate?;
+        NaiveDate::from_ymd_opt(year, birthdate.month(), birthdate.day()).or_else(|| {
+            match self.leap_day_policy {
+                LeapDayPolicy::February28 => NaiveDate::from_ymd_opt(year, 2, 28),
+                LeapDayPolicy::March1 => NaiveDate::from_ymd_opt(year, 3, 1),
+            }
+        })
+    }
+
+    /// Birthday on which the given age is reached.
+    pub fn birthday_at_age(&self, years: i32) -> Option<NaiveDate> {
+        self.birthday_in(self.birthdate?.year().checked_add(years)?)
+    }
+
+    pub fn is_birthday(&self, date: NaiveDate) -> bool {
+        self.birthday_in(date.year()) == Some(date)
+    }
+}
+
+//================================================== HIGHLIGHTS ==================================================
+
+/// User defined recurring day, i.e. paydays or anniversaries.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Highlight {
+    pub name: String,
+    pub start: NaiveDate,
+    pub rule: RecurrenceRule,
+    pub color: Color,
+    pub color_elapsed: Color,
+}
+
+//================================================== PERSISTENCE ==================================================
+
+impl Config {
+    /// Config file in the user's config directory, shared by the app and the command line modes.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("lyfcal").join("config.json"))
+    }
+
+    /// Saved config, or the default when nothing was saved yet. An elapsed date following now is moved to today.
+    pub fn load(clock: &dyn Clock) -> Result<Self, LyfcalError> {
+        let Some(path) = Self::path().filter(|path| path.exists()) else {
+            return Ok(Self::new(clock));
+        };
+        let text = std::fs::read_to_string(&path).map_err(|error| LyfcalError::Io {
+            action: "read",
+            path: path.clone(),
+            message: error.to_string(),
+        })?;
+        let mut config: Self = serde_json::from_str(&text).map_err(|error| LyfcalError::Parse {
+            path: path.clone(),
+            message: error.to_string(),
+        })?;
+        if config.elapsed_date_bool {
+            config.elapsed_date = clock.today();
+        }
+        Ok(config)
+    }
+
+    /// Written to a temporary file first so a crash never leaves a half written config.
+    pub fn save(&self) -> Result<(), LyfcalError> {
+        save_json(self, &Self::path().ok_or(LyfcalError::NoConfigDirectory)?)
+    }
+}
+
+/// Pretty printed JSON of the value, written next to the path and renamed over it.
+pub(crate) fn save_json(value: &impl Serialize, path: &Path) -> Result<(), LyfcalError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|error| LyfcalError::Io {
+            action: "create",
+            path: parent.to_path_buf(),
+            message: error.to_string(),
+        })?;
+    }
+    let text = serde_json::to_string_pretty(value).map_err(|error| LyfcalError::Io {
+        action: "serialize",
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    })?;
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    std::fs::write(&temporary, text)
+        .and_then(|_| std::fs::rename(&temporary, path))
+        .map_err(|error| LyfcalError::Io {
+            action: "write",
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        })
+}
+
+//Maps with non-string keys are stored as lists of entries, since JSON object keys must be strings.
+fn serialize_entries<S, K, V>(map: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    K: Serialize,
+    V: Serialize,
+{
+    serializer.collect_seq(map)
+}
+
+//Placements saved before monitors had an origin are keyed by size alone, and kept as on the monitor at the origin.
+fn deserialize_window_geometry<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<Monitor, WindowGeometry>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredMonitor {
+        Monitor(Monitor),
+        Size(u32, u32),
+    }
+
+    Ok(
+        Vec::<(StoredMonitor, WindowGeometry)>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(monitor, geometry)| match monitor {
+                StoredMonitor::Monitor(monitor) => (monitor, geometry),
+                StoredMonitor::Size(width, height) => (
+                    Monitor {
+                        origin: (0, 0),
+                        size: (width, height),
+                    },
+                    geometry,
+                ),
+            })
+            .collect(),
+    )
+}
+
+//================================================== WINDOW OPTIONS ==================================================
+
+/// Monitor told apart by where it is on the desktop as well as its size, both in points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Monitor {
+    pub origin: (i32, i32),
+    pub size: (u32, u32),
+}
+
+impl Monitor {
+    /// The monitor of the given size holding a point on the desktop. Only the size of a window's monitor
+    /// is known, so monitors are taken to lie side by side in a grid of that size, which tells apart
+    /// monitors of the same size.
+    pub fn containing(point: Pos2, size: Vec2) -> Self {
+        let width = size.x.round().max(1.0);
+        let height = size.y.round().max(1.0);
+        Self {
+            origin: (
+                ((point.x / width).floor() * width) as i32,
+                ((point.y / height).floor() * height) as i32,
+            ),
+            size: (width as u32, height as u32),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub position: Pos2,
+    pub inner_size: Vec2,
+}
+
+/// Stacking of the life viewport relative to other windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowLayer {
+    Below,
+    Normal,
+    Above,
+}
+
+impl WindowLayer {
+    pub const ALL: [Self; 3] = [Self::Below, Self::Normal, Self::Above];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Below => "below windows",
+            Self::Normal => "normal",
+            Self::Above => "above windows",
+        }
+    }
+}
+
+//================================================== LAYOUT OPTIONS ==================================================
+
+/// Direction each 7 day week strip is laid out in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeekOrientation {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HorizontalFlow {
+    LeftToRight,
+    RightToLeft,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerticalFlow {
+    TopDown,
+    BottomUp,
+}
+
+/// Order in which consecutive weeks fill the grid of columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlowMode {
+    /// Each column is filled before moving to the next.
+    Linear,
+    /// Every other column runs in reverse so consecutive weeks stay adjacent.
+    Snake,
+    /// Weeks wind clockwise from the first corner towards the centre.
+    Spiral,
+}
+
+impl WeekOrientation {
+    pub const ALL: [Self; 2] = [Self::Horizontal, Self::Vertical];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Horizontal => "horizontal",
+            Self::Vertical => "vertical",
+        }
+    }
+}
+
+impl HorizontalFlow {
+    pub const ALL: [Self; 2] = [Self::LeftToRight, Self::RightToLeft];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::LeftToRight => "left to right",
+            Self::RightToLeft => "right to left",
+        }
+    }
+}
+
+impl VerticalFlow {
+    pub const ALL: [Self; 2] = [Self::TopDown, Self::BottomUp];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::TopDown => "top down",
+            Self::BottomUp => "bottom up",
+        }
+    }
+}
+
+impl FlowMode {
+    pub const ALL: [Self; 3] = [Self::Linear, Self::Snake, Self::Spiral];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Snake => "snake",
+            Self::Spiral => "spiral",
+        }
+    }
+}
+
+/// What the layout solver holds fixed, the rest being solved for the available area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GridSizing {
+    /// Columns and unit size giving the largest units that fit both sides of the area.
+    Fit,
+    /// The configured unit size, as many columns and rows as fit, hiding the days beyond them.
+    FixedUnitSize,
+    /// The configured number of columns, with the largest units that fit.
+    FixedColumns,
+}
+
+/// Where the grid goes when it doesn't fill the area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GridAlignment {
+    /// Against the corner the grid flows from.
+    Start,
+    Center,
+    /// Columns and rows spread to the edges of the area.
+    Justify,
+}
+
+impl GridSizing {
+    pub const ALL: [Self; 3] = [Self::Fit, Self::FixedUnitSize, Self::FixedColumns];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Fit => "fit",
+            Self::FixedUnitSize => "fixed unit size",
+            Self::FixedColumns => "fixed columns",
+        }
+    }
+}
+
+impl GridAlignment {
+    pub const ALL: [Self; 3] = [Self::Start, Self::Center, Self::Justify];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Center => "center",
+            Self::Justify => "justify",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn leap_day_config(birthdate: NaiveDate, leap_day_policy: LeapDayPolicy) -> Config {
+        Config {
+            birthdate: Some(birthdate),
+            leap_day_policy,
+            ..testing::config()
+        }
+    }
+
+    #[test]
+    fn leap_day_birthday_follows_policy_in_common_years() {
+        let february = leap_day_config(date(1896, 2, 29), LeapDayPolicy::February28);
+        let march = leap_day_config(date(1896, 2, 29), LeapDayPolicy::March1);
+        //1900 and 2100 are centuries skipped as leap years, 2000 is divisible by 400 and kept.
+        for (year, leap) in [
+            (1900, false),
+            (1904, true),
+            (2000, true),
+            (2023, false),
+            (2100, false),
+        ] {
+            if leap {
+                assert_eq!(february.birthday_in(year), Some(date(year, 2, 29)));
+                assert_eq!(march.birthday_in(year), Some(date(year, 2, 29)));
+            } else {
+                assert_eq!(february.birthday_in(year), Some(date(year, 2, 28)));
+                assert_eq!(march.birthday_in(year), Some(date(year, 3, 1)));
+            }
+        }
+    }
+
+    #[test]
+    fn leap_day_birthday_is_celebrated_once_a_year() {
+        let february = leap_day_config(date(2000, 2, 29), LeapDayPolicy::February28);
+        let march = leap_day_config(date(2000, 2, 29), LeapDayPolicy::March1);
+        assert!(february.is_birthday(date(2100, 2, 28)));
+        assert!(!february.is_birthday(date(2100, 3, 1)));
+        assert!(march.is_birthday(date(2100, 3, 1)));
+        assert!(!march.is_birthday(date(2100, 2, 28)));
+        for config in [&february, &march] {
+            assert!(config.is_birthday(date(2400, 2, 29)));
+            assert!(!config.is_birthday(date(2400, 2, 28)));
+            assert!(!config.is_birthday(date(2400, 3, 1)));
+        }
+    }
+
+    #[test]
+    fn other_birthdates_ignore_leap_day_policy() {
+        for policy in LeapDayPolicy::ALL {
+            let config = leap_day_config(date(1999, 3, 1), policy);
+            assert_eq!(config.birthday_in(2100), Some(date(2100, 3, 1)));
+            assert!(!config.is_birthday(date(2100, 2, 28)));
+            let config = leap_day_config(date(1999, 2, 28), policy);
+            assert_eq!(config.birthday_in(2000), Some(date(2000, 2, 28)));
+            assert!(!config.is_birthday(date(2000, 2, 29)));
+        }
+    }
+
+    #[test]
+    fn lifespan_ends_on_birthday_under_policy() {
+        let lifespan = |birthdate, life_expectancy, policy| {
+            Config {
+                life_expectancy,
+                ..leap_day_config(birthdate, policy)
+            }
+            .lifespan()
+            .unwrap()
+            .1
+        };
+        assert_eq!(
+            lifespan(date(2000, 2, 29), 100, LeapDayPolicy::February28),
+            date(2100, 2, 28)
+        );
+        assert_eq!(
+            lifespan(date(2000, 2, 29), 100, LeapDayPolicy::March1),
+            date(2100, 3, 1)
+        );
+        for policy in LeapDayPolicy::ALL {
+            assert_eq!(lifespan(date(1896, 2, 29), 104, policy), date(2000, 2, 29));
+            assert_eq!(lifespan(date(1904, 2, 29), 96, policy), date(2000, 2, 29));
+        }
+    }
+
+    #[test]
+    fn monitors_of_the_same_size_are_told_apart() {
+        let size = Vec2 {
+            x: 1920.0,
+            y: 1080.0,
+        };
+        let monitor = |x, y| Monitor::containing(Pos2 { x, y }, size);
+        assert_eq!(monitor(960.0, 540.0).origin, (0, 0));
+        assert_eq!(monitor(2880.0, 540.0).origin, (1920, 0));
+        assert_eq!(monitor(-960.0, 540.0).origin, (-1920, 0));
+        assert_eq!(monitor(2880.0, 540.0).size, (1920, 1080));
+        assert_ne!(monitor(960.0, 540.0), monitor(2880.0, 540.0));
+    }
+
+    #[test]
+    fn placements_keyed_by_size_alone_are_kept() {
+        let json = r#"[
+            [[1920, 1080], {"position": {"x": 10.0, "y": 20.0}, "inner_size": {"x": 300.0, "y": 200.0}}],
+            [{"origin": [1920, 0], "size": [1920, 1080]}, {"position": {"x": 1930.0, "y": 20.0}, "inner_size": {"x": 300.0, "y": 200.0}}]
+        ]"#;
+        let geometry =
+            deserialize_window_geometry(&mut serde_json::Deserializer::from_str(json)).unwrap();
+        let origins: Vec<_> = geometry.keys().map(|monitor| monitor.origin).collect();
+        assert_eq!(origins, [(0, 0), (1920, 0)]);
+        assert!(geometry.keys().all(|monitor| monitor.size == (1920, 1080)));
+    }
+}
@@ -0,0 +1,123 @@
+//! Everything that can go wrong building a calendar from a config, reading and writing files, and in the front ends.
+
+use chrono::NaiveDate;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+/// Oldest life expectancy and retirement age accepted, in years.
+pub const MAX_AGE: i32 = 120;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LyfcalError {
+    /// No birthdate is set, so there is no lifespan to lay out.
+    MissingBirthdate,
+    LifeExpectancy(i32),
+    RetirementAge(i32),
+    /// The lifespan ends after the last date that can be represented.
+    LifespanOutOfRange {
+        birthdate: NaiveDate,
+        life_expectancy: i32,
+    },
+    /// A layout setting outside the values the grid can be laid out with.
+    OutOfRange {
+        setting: &'static str,
+        value: f32,
+        range: RangeInclusive<f32>,
+    },
+    NoConfigDirectory,
+    /// Reading or writing a file failed.
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        message: String,
+    },
+    /// A file was read but its contents aren't valid.
+    Parse {
+        path: PathBuf,
+        message: String,
+    },
+    /// A theme can't be saved under this name.
+    ThemeName {
+        name: String,
+        problem: &'static str,
+    },
+    NoDataDirectory,
+    /// Command line arguments or a forwarded command that can't be understood.
+    Usage(String),
+    /// A command forwarded to the running instance failed there, with the reason it sent back.
+    Remote(String),
+    /// The running instance took a command but never answered.
+    NoResponse,
+    /// Drawing to or reading keys from the terminal, or writing to standard output, failed.
+    Terminal(String),
+    NoWallpaperPath,
+    /// The monitor the wallpaper is for hasn't reported its size yet.
+    UnknownScreenSize,
+    WallpaperPanicked,
+    /// The command run on a new wallpaper couldn't be started.
+    Hook {
+        command: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for LyfcalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingBirthdate => write!(f, "no birthdate is set"),
+            Self::LifeExpectancy(years) => write!(
+                f,
+                "a life expectancy of {} years is outside 1 to {}",
+                years, MAX_AGE
+            ),
+            Self::RetirementAge(years) => write!(
+                f,
+                "a retirement age of {} years is outside 1 to {}",
+                years, MAX_AGE
+            ),
+            Self::LifespanOutOfRange {
+                birthdate,
+                life_expectancy,
+            } => write!(
+                f,
+                "{} years from {} runs past the last supported date",
+                life_expectancy, birthdate
+            ),
+            Self::OutOfRange {
+                setting,
+                value,
+                range,
+            } => write!(
+                f,
+                "{} of {} is outside {} to {}",
+                setting,
+                value,
+                range.start(),
+                range.end()
+            ),
+            Self::NoConfigDirectory => write!(f, "no config directory"),
+            Self::Io {
+                action,
+                path,
+                message,
+            } => write!(f, "failed to {} {}: {}", action, path.display(), message),
+            Self::Parse { path, message } => {
+                write!(f, "failed to parse {}: {}", path.display(), message)
+            }
+            Self::ThemeName { name, problem } => write!(f, "theme name \"{}\" {}", name, problem),
+            Self::NoDataDirectory => write!(f, "no data directory"),
+            Self::Usage(message) | Self::Remote(message) => write!(f, "{}", message),
+            Self::NoResponse => write!(f, "lyfcal didn't respond"),
+            Self::Terminal(message) => write!(f, "terminal: {}", message),
+            Self::NoWallpaperPath => write!(f, "no wallpaper path set"),
+            Self::UnknownScreenSize => write!(f, "screen size is unknown"),
+            Self::WallpaperPanicked => write!(f, "wallpaper rendering panicked"),
+            Self::Hook { command, message } => {
+                write!(f, "failed to run \"{}\": {}", command, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LyfcalError {}
@@ -0,0 +1,60 @@
+//! Minimal 2D geometry in logical points for laying out the grid, independent of any UI toolkit.
+
+use serde::{Deserialize, Serialize};
+
+/// A position in points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pos2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A size or offset in points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// An axis aligned rectangle from its top left to its bottom right corner.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rect {
+    pub min: Pos2,
+    pub max: Pos2,
+}
+
+pub const fn pos2(x: f32, y: f32) -> Pos2 {
+    Pos2 { x, y }
+}
+
+pub const fn vec2(x: f32, y: f32) -> Vec2 {
+    Vec2 { x, y }
+}
+
+impl Rect {
+    pub fn from_min_size(min: Pos2, size: Vec2) -> Self {
+        Self {
+            min,
+            max: pos2(min.x + size.x, min.y + size.y),
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn size(&self) -> Vec2 {
+        vec2(self.width(), self.height())
+    }
+
+    pub fn center(&self) -> Pos2 {
+        pos2(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+        )
+    }
+}
@@ -0,0 +1,503 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use HolidayRule::*;
+use Observance::*;
+
+/// How a date is produced for a given year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HolidayRule {
+    /// Same month and day every year.
+    Fixed { month: u32, day: u32 },
+    /// nth (1 based) occurrence of a weekday in a month.
+    NthWeekday { month: u32, weekday: Weekday, n: u8 },
+    /// Final occurrence of a weekday in a month.
+    LastWeekday { month: u32, weekday: Weekday },
+    /// Last occurrence of a weekday on or before a date, i.e. Victoria Day.
+    WeekdayOnOrBefore {
+        month: u32,
+        day: u32,
+        weekday: Weekday,
+    },
+    /// Days relative to Easter Sunday.
+    EasterRelative { offset: i64 },
+}
+
+/// What happens when a holiday lands on a Saturday or Sunday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Observance {
+    /// The holiday stays on the actual date.
+    Actual,
+    /// Saturday moves back to Friday and Sunday forward to Monday.
+    NearestWeekday,
+    /// Moves forward to the next weekday that isn't already a holiday.
+    Substitute,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Holiday {
+    pub name: &'static str,
+    pub rule: HolidayRule,
+    pub observance: Observance,
+    /// First year the holiday was observed.
+    pub since: i32,
+}
+
+impl Holiday {
+    const fn new(name: &'static str, rule: HolidayRule, observance: Observance) -> Self {
+        Self {
+            name,
+            rule,
+            observance,
+            since: i32::MIN,
+        }
+    }
+
+    const fn since(mut self, year: i32) -> Self {
+        self.since = year;
+        self
+    }
+
+    /// Date the rule falls on in the given year, before any observance shift.
+    pub fn actual_date(&self, year: i32) -> Option<NaiveDate> {
+        if year < self.since {
+            return None;
+        }
+        match self.rule {
+            HolidayRule::Fixed { month, day } => NaiveDate::from_ymd_opt(year, month, day),
+            HolidayRule::NthWeekday { month, weekday, n } => {
+                NaiveDate::from_weekday_of_month_opt(year, month, weekday, n)
+            }
+            HolidayRule::LastWeekday { month, weekday } => {
+                let last_day = last_day_of_month(year, month)?;
+                let days_back = last_day.weekday().days_since(weekday);
+                Some(last_day - Duration::days(days_back as i64))
+            }
+            HolidayRule::WeekdayOnOrBefore {
+                month,
+                day,
+                weekday,
+            } => {
+                let date = NaiveDate::from_ymd_opt(year, month, day)?;
+                let days_back = date.weekday().days_since(weekday);
+                Some(date - Duration::days(days_back as i64))
+            }
+            HolidayRule::EasterRelative { offset } => {
+                Some(easter_sunday(year)? + Duration::days(offset))
+            }
+        }
+    }
+}
+
+//================================================== REGIONS ==================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HolidayRegion {
+    EnglandWales,
+    UnitedStates,
+    Canada,
+    Australia,
+    Germany,
+    France,
+}
+
+impl HolidayRegion {
+    pub const ALL: [Self; 6] = [
+        Self::EnglandWales,
+        Self::UnitedStates,
+        Self::Canada,
+        Self::Australia,
+        Self::Germany,
+        Self::France,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::EnglandWales => "england & wales",
+            Self::UnitedStates => "united states",
+            Self::Canada => "canada",
+            Self::Australia => "australia",
+            Self::Germany => "germany",
+            Self::France => "france",
+        }
+    }
+
+    pub fn holidays(&self) -> &'static [Holiday] {
+        match self {
+            Self::EnglandWales => ENGLAND_WALES,
+            Self::UnitedStates => UNITED_STATES,
+            Self::Canada => CANADA,
+            Self::Australia => AUSTRALIA,
+            Self::Germany => GERMANY,
+            Self::France => FRANCE,
+        }
+    }
+
+    /// Observed holidays for a single year.
+    pub fn holidays_in_year(&self, year: i32) -> BTreeMap<NaiveDate, &'static str> {
+        let mut output = BTreeMap::new();
+        let mut substitutes = Vec::new();
+
+        for holiday in self.holidays() {
+            let Some(date) = holiday.actual_date(year) else {
+                continue;
+            };
+            match (holiday.observance, date.weekday()) {
+                (Observance::NearestWeekday, Weekday::Sat) => {
+                    output.insert(date - Duration::days(1), holiday.name);
+                }
+                (Observance::NearestWeekday, Weekday::Sun) => {
+                    output.insert(date + Duration::days(1), holiday.name);
+                }
+                (Observance::Substitute, Weekday::Sat | Weekday::Sun) => {
+                    substitutes.push((date, holiday.name));
+                }
+                _ => {
+                    output.insert(date, holiday.name);
+                }
+            }
+        }
+
+        //Substitutes are resolved last so they skip over every other holiday of the year.
+        substitutes.sort();
+        for (date, name) in substitutes {
+            let mut observed = date;
+            while is_saturday_or_sunday(observed) || output.contains_key(&observed) {
+                observed += Duration::days(1);
+            }
+            output.insert(observed, name);
+        }
+        output
+    }
+
+    /// Observed holidays between two dates inclusive.
+    pub fn holidays_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> BTreeMap<NaiveDate, &'static str> {
+        //Neighbouring years are included since observed dates can cross into another year.
+        (start.year() - 1..=end.year() + 1)
+            .flat_map(|year| self.holidays_in_year(year))
+            .filter(|(date, _)| *date >= start && *date <= end)
+            .collect()
+    }
+}
+
+const ENGLAND_WALES: &[Holiday] = &[
+    Holiday::new("New Year's Day", Fixed { month: 1, day: 1 }, Substitute),
+    Holiday::new("Good Friday", EasterRelative { offset: -2 }, Actual),
+    Holiday::new("Easter Monday", EasterRelative { offset: 1 }, Actual),
+    Holiday::new(
+        "Early May bank holiday",
+        NthWeekday {
+            month: 5,
+            weekday: Weekday::Mon,
+            n: 1,
+        },
+        Actual,
+    )
+    .since(1978),
+    Holiday::new(
+        "Spring bank holiday",
+        LastWeekday {
+            month: 5,
+            weekday: Weekday::Mon,
+        },
+        Actual,
+    )
+    .since(1971),
+    Holiday::new(
+        "Summer bank holiday",
+        LastWeekday {
+            month: 8,
+            weekday: Weekday::Mon,
+        },
+        Actual,
+    )
+    .since(1971),
+    Holiday::new("Christmas Day", Fixed { month: 12, day: 25 }, Substitute),
+    Holiday::new("Boxing Day", Fixed { month: 12, day: 26 }, Substitute),
+];
+
+const UNITED_STATES: &[Holiday] = &[
+    Holiday::new("New Year's Day", Fixed { month: 1, day: 1 }, NearestWeekday),
+    Holiday::new(
+        "Martin Luther King Jr. Day",
+        NthWeekday {
+            month: 1,
+            weekday: Weekday::Mon,
+            n: 3,
+        },
+        Actual,
+    )
+    .since(1986),
+    Holiday::new(
+        "Washington's Birthday",
+        NthWeekday {
+            month: 2,
+            weekday: Weekday::Mon,
+            n: 3,
+        },
+        Actual,
+    ),
+    Holiday::new(
+        "Memorial Day",
+        LastWeekday {
+            month: 5,
+            weekday: Weekday::Mon,
+        },
+        Actual,
+    ),
+    Holiday::new("Juneteenth", Fixed { month: 6, day: 19 }, NearestWeekday).since(2021),
+    Holiday::new(
+        "Independence Day",
+        Fixed { month: 7, day: 4 },
+        NearestWeekday,
+    ),
+    Holiday::new(
+        "Labor Day",
+        NthWeekday {
+            month: 9,
+            weekday: Weekday::Mon,
+            n: 1,
+        },
+        Actual,
+    ),
+    Holiday::new(
+        "Columbus Day",
+        NthWeekday {
+            month: 10,
+            weekday: Weekday::Mon,
+            n: 2,
+        },
+        Actual,
+    ),
+    Holiday::new("Veterans Day", Fixed { month: 11, day: 11 }, NearestWeekday),
+    Holiday::new(
+        "Thanksgiving Day",
+        NthWeekday {
+            month: 11,
+            weekday: Weekday::Thu,
+            n: 4,
+        },
+        Actual,
+    ),
+    Holiday::new(
+        "Christmas Day",
+        Fixed { month: 12, day: 25 },
+        NearestWeekday,
+    ),
+];
+
+const CANADA: &[Holiday] = &[
+    Holiday::new("New Year's Day", Fixed { month: 1, day: 1 }, Substitute),
+    Holiday::new("Good Friday", EasterRelative { offset: -2 }, Actual),
+    Holiday::new(
+        "Victoria Day",
+        WeekdayOnOrBefore {
+            month: 5,
+            day: 24,
+            weekday: Weekday::Mon,
+        },
+        Actual,
+    ),
+    Holiday::new("Canada Day", Fixed { month: 7, day: 1 }, Substitute),
+    Holiday::new(
+        "Labour Day",
+        NthWeekday {
+            month: 9,
+            weekday: Weekday::Mon,
+            n: 1,
+        },
+        Actual,
+    ),
+    Holiday::new(
+        "Thanksgiving",
+        NthWeekday {
+            month: 10,
+            weekday: Weekday::Mon,
+            n: 2,
+        },
+        Actual,
+    ),
+    Holiday::new("Christmas Day", Fixed { month: 12, day: 25 }, Substitute),
+    Holiday::new("Boxing Day", Fixed { month: 12, day: 26 }, Substitute),
+];
+
+const AUSTRALIA: &[Holiday] = &[
+    Holiday::new("New Year's Day", Fixed { month: 1, day: 1 }, Substitute),
+    Holiday::new("Australia Day", Fixed { month: 1, day: 26 }, Substitute),
+    Holiday::new("Good Friday", EasterRelative { offset: -2 }, Actual),
+    Holiday::new("Easter Monday", EasterRelative { offset: 1 }, Actual),
+    Holiday::new("Anzac Day", Fixed { month: 4, day: 25 }, Actual),
+    Holiday::new(
+        "King's Birthday",
+        NthWeekday {
+            month: 6,
+            weekday: Weekday::Mon,
+            n: 2,
+        },
+        Actual,
+    ),
+    Holiday::new("Christmas Day", Fixed { month: 12, day: 25 }, Substitute),
+    Holiday::new("Boxing Day", Fixed { month: 12, day: 26 }, Substitute),
+];
+
+const GERMANY: &[Holiday] = &[
+    Holiday::new("Neujahr", Fixed { month: 1, day: 1 }, Actual),
+    Holiday::new("Karfreitag", EasterRelative { offset: -2 }, Actual),
+    Holiday::new("Ostermontag", EasterRelative { offset: 1 }, Actual),
+    Holiday::new("Tag der Arbeit", Fixed { month: 5, day: 1 }, Actual),
+    Holiday::new("Christi Himmelfahrt", EasterRelative { offset: 39 }, Actual),
+    Holiday::new("Pfingstmontag", EasterRelative { offset: 50 }, Actual),
+    Holiday::new(
+        "Tag der Deutschen Einheit",
+        Fixed { month: 10, day: 3 },
+        Actual,
+    )
+    .since(1990),
+    Holiday::new("1. Weihnachtstag", Fixed { month: 12, day: 25 }, Actual),
+    Holiday::new("2. Weihnachtstag", Fixed { month: 12, day: 26 }, Actual),
+];
+
+const FRANCE: &[Holiday] = &[
+    Holiday::new("Jour de l'an", Fixed { month: 1, day: 1 }, Actual),
+    Holiday::new("Lundi de Pâques", EasterRelative { offset: 1 }, Actual),
+    Holiday::new("Fête du Travail", Fixed { month: 5, day: 1 }, Actual),
+    Holiday::new("Victoire 1945", Fixed { month: 5, day: 8 }, Actual),
+    Holiday::new("Ascension", EasterRelative { offset: 39 }, Actual),
+    Holiday::new("Lundi de Pentecôte", EasterRelative { offset: 50 }, Actual),
+    Holiday::new("Fête nationale", Fixed { month: 7, day: 14 }, Actual),
+    Holiday::new("Assomption", Fixed { month: 8, day: 15 }, Actual),
+    Holiday::new("Toussaint", Fixed { month: 11, day: 1 }, Actual),
+    Holiday::new("Armistice 1918", Fixed { month: 11, day: 11 }, Actual),
+    Holiday::new("Noël", Fixed { month: 12, day: 25 }, Actual),
+];
+
+//================================================== MISC. FUNCTIONS ==================================================
+
+/// Anonymous Gregorian algorithm (Meeus/Jones/Butcher).
+pub fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)?.pred_opt()
+}
+
+fn is_saturday_or_sunday(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn observed(region: HolidayRegion, name: &str, year: i32) -> Vec<NaiveDate> {
+        region
+            .holidays_between(date(year, 1, 1), date(year, 12, 31))
+            .into_iter()
+            .filter(|(_, holiday)| *holiday == name)
+            .map(|(date, _)| date)
+            .collect()
+    }
+
+    #[test]
+    fn easter_sunday_matches_known_dates() {
+        for easter in [
+            date(1818, 3, 22),
+            date(2000, 4, 23),
+            date(2024, 3, 31),
+            date(2025, 4, 20),
+            date(2038, 4, 25),
+        ] {
+            assert_eq!(easter_sunday(easter.year()), Some(easter));
+        }
+    }
+
+    #[test]
+    fn substitutes_chain_past_each_other() {
+        //Both days on the weekend move to the following Monday and Tuesday.
+        assert_eq!(
+            observed(HolidayRegion::EnglandWales, "Christmas Day", 2021),
+            [date(2021, 12, 27)]
+        );
+        assert_eq!(
+            observed(HolidayRegion::EnglandWales, "Boxing Day", 2021),
+            [date(2021, 12, 28)]
+        );
+        //Boxing Day keeps its Monday, so Christmas skips over it to Tuesday.
+        assert_eq!(
+            observed(HolidayRegion::EnglandWales, "Christmas Day", 2022),
+            [date(2022, 12, 27)]
+        );
+        assert_eq!(
+            observed(HolidayRegion::EnglandWales, "Boxing Day", 2022),
+            [date(2022, 12, 26)]
+        );
+    }
+
+    #[test]
+    fn nearest_weekday_can_move_into_the_previous_year() {
+        //1 January 2022 is a Saturday, observed on Friday 31 December 2021.
+        let holidays = HolidayRegion::UnitedStates.holidays_in_year(2022);
+        assert_eq!(holidays.get(&date(2021, 12, 31)), Some(&"New Year's Day"));
+        assert!(!holidays.contains_key(&date(2022, 1, 1)));
+        assert_eq!(
+            observed(HolidayRegion::UnitedStates, "New Year's Day", 2021),
+            [date(2021, 1, 1), date(2021, 12, 31)]
+        );
+        assert_eq!(
+            observed(HolidayRegion::UnitedStates, "New Year's Day", 2022),
+            []
+        );
+    }
+
+    #[test]
+    fn holidays_start_in_their_first_year() {
+        for (region, name, since) in [
+            (HolidayRegion::UnitedStates, "Juneteenth", 2021),
+            (
+                HolidayRegion::UnitedStates,
+                "Martin Luther King Jr. Day",
+                1986,
+            ),
+            (HolidayRegion::EnglandWales, "Early May bank holiday", 1978),
+            (HolidayRegion::Germany, "Tag der Deutschen Einheit", 1990),
+        ] {
+            for year in [since - 10, since - 1] {
+                assert_eq!(observed(region, name, year), [], "{} {}", name, year);
+            }
+            assert_eq!(observed(region, name, since).len(), 1, "{} {}", name, since);
+        }
+        assert_eq!(
+            observed(HolidayRegion::UnitedStates, "Juneteenth", 2021),
+            [date(2021, 6, 18)]
+        );
+    }
+}
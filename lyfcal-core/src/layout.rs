@@ -0,0 +1,737 @@
+//! Geometry of the life grid: how many week strips fit an area and where each day's unit goes.
+
+use super::calendar::Calendar;
+use super::config::{
+    FlowMode, GridAlignment, GridSizing, HorizontalFlow, VerticalFlow, WeekOrientation,
+};
+use super::geometry::{pos2, vec2, Rect, Vec2};
+
+/// Columns and rows of week strips fitting an area, the size of a single unit and where the grid sits in the area.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Matrix {
+    /// Each column contains 7 units for each day of the week.
+    pub col: usize,
+    pub row: usize,
+    pub unit_size: f32,
+    /// Space before the first column, along the cross axis, and before the first row, along the main axis.
+    pub cross_margin: f32,
+    pub main_margin: f32,
+    /// Space added between neighbouring columns and rows on top of the configured spacing.
+    pub col_gap: f32,
+    pub row_gap: f32,
+}
+
+/// How well a grid uses its area.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct LayoutMetrics {
+    /// Share of the area covered by the cells of the days drawn, spacing and leftover space excluded.
+    pub fill_ratio: f32,
+    /// Square points of the area left over around the grid, which the alignment distributes.
+    pub wasted_area: f32,
+    /// Week slots of the grid holding no day, before the birthdate and after the last week.
+    pub empty_slots: usize,
+    /// Days left out because the grid can't hold them, only with a fixed unit size.
+    pub hidden_days: usize,
+}
+
+/// Laid out grid of a calendar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridLayout {
+    pub unit_size: f32,
+    /// Rect of each event in date order, events that don't fit the grid have none.
+    pub units: Vec<Rect>,
+    pub metrics: LayoutMetrics,
+}
+
+impl Calendar {
+    /// Solve for the columns, rows and unit size of the grid within the available space, following the
+    /// configured sizing, then place the grid within the space following the configured alignment.
+    pub fn calculate_matrix(&self, available: Vec2) -> Matrix {
+        //Columns of 7 day strips are laid along the cross axis, weeks stack along the main axis.
+        let (cross_len, main_len) = self.axis_lengths(available);
+        let week_num = self.week_count();
+        //Nothing fits an empty area.
+        if !(cross_len > 0.0 && main_len > 0.0) || week_num == 0 {
+            return Matrix::default();
+        }
+
+        let (col, row, unit_size) = match self.config.grid_sizing {
+            //Every column count is tried, the unit size being the largest fitting both axes.
+            GridSizing::Fit => (1..=week_num)
+                .map(|col_num| {
+                    let row_num = week_num.div_ceil(col_num);
+                    (
+                        col_num,
+                        row_num,
+                        self.fitting_unit_size(cross_len, main_len, col_num, row_num),
+                    )
+                })
+                .fold((0, 0, 0.0), |best, candidate| {
+                    if candidate.2 > best.2 {
+                        candidate
+                    } else {
+                        best
+                    }
+                }),
+            GridSizing::FixedColumns => {
+                let col_num = self.config.column_count.clamp(1, week_num);
+                let row_num = week_num.div_ceil(col_num);
+                (
+                    col_num,
+                    row_num,
+                    self.fitting_unit_size(cross_len, main_len, col_num, row_num),
+                )
+            }
+            //As many columns and rows as fit at the given size, days past the last slot are hidden.
+            GridSizing::FixedUnitSize => {
+                let unit_size = self.config.unit_size;
+                let max_col =
+                    self.fitting_count(cross_len, unit_size, 7.0, self.config.col_spacing);
+                let max_row = self.fitting_count(main_len, unit_size, 1.0, self.config.row_spacing);
+                if max_col == 0 || max_row == 0 {
+                    return Matrix::default();
+                }
+                //The fewest columns holding every week, so the grid grows along the main axis first.
+                let col_num = week_num.div_ceil(max_row).min(max_col);
+                let row_num = week_num.div_ceil(col_num).min(max_row);
+                (col_num, row_num, unit_size)
+            }
+        };
+        self.place_matrix(col, row, unit_size, cross_len, main_len)
+    }
+
+    /// Grid at the given unit size spanning `width`, growing downwards as far as the lifespan needs, for
+    /// views that scroll. Everything but the configured sizing is followed, as there is no height to fit.
+    pub fn scrolling_layout(&self, width: f32, unit_size: f32) -> GridLayout {
+        let week_num = self.week_count();
+        if !(width > 0.0 && unit_size > 0.0) || week_num == 0 {
+            return GridLayout {
+                unit_size: 0.0,
+                units: Vec::new(),
+                metrics: LayoutMetrics::default(),
+            };
+        }
+        //The width holds the columns of week strips, or with vertical weeks the rows.
+        let (col, row) = match self.config.week_orientation {
+            WeekOrientation::Horizontal => {
+                let col_num = self
+                    .fitting_count(width, unit_size, 7.0, self.config.col_spacing)
+                    .clamp(1, week_num);
+                (col_num, week_num.div_ceil(col_num))
+            }
+            WeekOrientation::Vertical => {
+                let row_num = self
+                    .fitting_count(width, unit_size, 1.0, self.config.row_spacing)
+                    .clamp(1, week_num);
+                (week_num.div_ceil(row_num), row_num)
+            }
+        };
+        let (grid_cross, grid_main) = self.grid_extent(col, row, unit_size);
+        let available = match self.config.week_orientation {
+            WeekOrientation::Horizontal => vec2(width, grid_main),
+            WeekOrientation::Vertical => vec2(width, grid_cross),
+        };
+        let (cross_len, main_len) = self.axis_lengths(available);
+        let matrix = self.place_matrix(col, row, unit_size, cross_len, main_len);
+        self.layout_matrix(
+            Rect::from_min_size(pos2(0.0, 0.0), available),
+            available,
+            &matrix,
+        )
+    }
+
+    //Place a grid of the given columns, rows and unit size within the space following the configured alignment.
+    fn place_matrix(
+        &self,
+        col: usize,
+        row: usize,
+        unit_size: f32,
+        cross_len: f32,
+        main_len: f32,
+    ) -> Matrix {
+        //Leftover space on either axis is placed according to the alignment.
+        let (grid_cross, grid_main) = self.grid_extent(col, row, unit_size);
+        let (cross_left, main_left) = (
+            (cross_len - grid_cross).max(0.0),
+            (main_len - grid_main).max(0.0),
+        );
+        let gap = |left: f32, count: usize| {
+            if count > 1 {
+                left / (count - 1) as f32
+            } else {
+                0.0
+            }
+        };
+        let (cross_margin, main_margin, col_gap, row_gap) = match self.config.grid_alignment {
+            GridAlignment::Start => (0.0, 0.0, 0.0, 0.0),
+            GridAlignment::Center => (cross_left / 2.0, main_left / 2.0, 0.0, 0.0),
+            //A lone column or row is centred, there being no gaps to spread it over.
+            GridAlignment::Justify => (
+                if col > 1 { 0.0 } else { cross_left / 2.0 },
+                if row > 1 { 0.0 } else { main_left / 2.0 },
+                gap(cross_left, col),
+                gap(main_left, row),
+            ),
+        };
+        Matrix {
+            col,
+            row,
+            unit_size,
+            cross_margin,
+            main_margin,
+            col_gap,
+            row_gap,
+        }
+    }
+
+    //Available size as (cross, main) lengths for the week orientation.
+    fn axis_lengths(&self, available: Vec2) -> (f32, f32) {
+        match self.config.week_orientation {
+            WeekOrientation::Horizontal => (available.x, available.y),
+            WeekOrientation::Vertical => (available.y, available.x),
+        }
+    }
+
+    //Columns or rows, each unit_len units long with spacing between them, fitting a length at a unit size.
+    fn fitting_count(&self, len: f32, unit_size: f32, unit_len: f32, spacing: f32) -> usize {
+        ((len / unit_size - 2.0 * self.config.border_spacing + spacing) / (unit_len + spacing))
+            .floor()
+            .max(0.0) as usize
+    }
+
+    //Cross and main lengths of a grid, borders included.
+    fn grid_extent(&self, col_num: usize, row_num: usize, unit_size: f32) -> (f32, f32) {
+        let (col_num, row_num) = (col_num as f32, row_num as f32);
+        (
+            unit_size
+                * (col_num * 7.0
+                    + self.config.col_spacing * (col_num - 1.0)
+                    + 2.0 * self.config.border_spacing),
+            unit_size
+                * (row_num
+                    + self.config.row_spacing * (row_num - 1.0)
+                    + 2.0 * self.config.border_spacing),
+        )
+    }
+
+    //Largest unit size fitting a grid of the given columns and rows on both axes.
+    fn fitting_unit_size(
+        &self,
+        cross_len: f32,
+        main_len: f32,
+        col_num: usize,
+        row_num: usize,
+    ) -> f32 {
+        let (cross_units, main_units) = self.grid_extent(col_num, row_num, 1.0);
+        (cross_len / cross_units).min(main_len / main_units)
+    }
+
+    /// (column, row) cell of every week in drawing order, following the configured flow mode.
+    pub fn week_cells(&self, matrix: &Matrix) -> Vec<(usize, usize)> {
+        match self.config.flow_mode {
+            FlowMode::Linear => (0..matrix.col)
+                .flat_map(|col| (0..matrix.row).map(move |row| (col, row)))
+                .collect(),
+            FlowMode::Snake => (0..matrix.col)
+                .flat_map(|col| {
+                    (0..matrix.row).map(move |row| {
+                        if col % 2 == 0 {
+                            (col, row)
+                        } else {
+                            (col, matrix.row - 1 - row)
+                        }
+                    })
+                })
+                .collect(),
+            FlowMode::Spiral => spiral_cells(matrix.col, matrix.row),
+        }
+    }
+
+    /// Calculate unit location.
+    pub fn calculate_pos(
+        &self,
+        area: Rect,
+        matrix: &Matrix,
+        cell: (usize, usize),
+        day: usize,
+    ) -> Rect {
+        let (col, row) = cell;
+        let unit_size = matrix.unit_size;
+        let cross = unit_size
+            * (self.config.border_spacing
+                + day as f32
+                + 7.0 * col as f32
+                + self.config.col_spacing * (col as f32))
+            + matrix.cross_margin
+            + col as f32 * matrix.col_gap;
+        let main = unit_size
+            * (self.config.border_spacing + row as f32 + self.config.row_spacing * (row as f32))
+            + matrix.main_margin
+            + row as f32 * matrix.row_gap;
+        let (mut x, mut y) = match self.config.week_orientation {
+            WeekOrientation::Horizontal => (cross, main),
+            WeekOrientation::Vertical => (main, cross),
+        };
+        if self.config.horizontal_flow == HorizontalFlow::RightToLeft {
+            x = area.width() - x - unit_size;
+        }
+        if self.config.vertical_flow == VerticalFlow::BottomUp {
+            y = area.height() - y - unit_size;
+        }
+        Rect::from_min_size(
+            pos2(
+                area.min.x + x + ((1.0 - self.config.unit_ratio) / 2.0) * unit_size,
+                area.min.y + y + ((1.0 - self.config.unit_ratio) / 2.0) * unit_size,
+            ),
+            vec2(
+                self.config.unit_ratio * unit_size,
+                self.config.unit_ratio * unit_size,
+            ),
+        )
+    }
+
+    /// Rect of every unit of the grid laid out within the given area.
+    pub fn grid_layout(&self, area: Rect, available: Vec2) -> GridLayout {
+        let matrix = self.calculate_matrix(available);
+        self.layout_matrix(area, available, &matrix)
+    }
+
+    fn layout_matrix(&self, area: Rect, available: Vec2, matrix: &Matrix) -> GridLayout {
+        //To allow exception to the first week shown where the week doesn't begin on the week start, the first week is offset.
+        let cells = self.week_cells(matrix);
+        let first_week_offset = self.first_week_offset();
+
+        let units: Vec<Rect> = (0..self.events.len())
+            .map_while(|index| {
+                let slot = index + first_week_offset;
+                let cell = *cells.get(slot / 7)?;
+                Some(self.calculate_pos(area, matrix, cell, slot % 7))
+            })
+            .collect();
+        GridLayout {
+            unit_size: matrix.unit_size,
+            metrics: self.layout_metrics(matrix, available, units.len()),
+            units,
+        }
+    }
+
+    fn layout_metrics(&self, matrix: &Matrix, available: Vec2, drawn: usize) -> LayoutMetrics {
+        let area = available.x.max(0.0) * available.y.max(0.0);
+        if area == 0.0 {
+            return LayoutMetrics {
+                hidden_days: self.events.len(),
+                ..Default::default()
+            };
+        }
+        let (grid_cross, grid_main) = self.grid_extent(matrix.col, matrix.row, matrix.unit_size);
+        let slots = matrix.col * matrix.row;
+        LayoutMetrics {
+            fill_ratio: drawn as f32 * matrix.unit_size * matrix.unit_size / area,
+            wasted_area: if slots == 0 {
+                area
+            } else {
+                (area - grid_cross * grid_main).max(0.0)
+            },
+            empty_slots: slots.saturating_sub((drawn + self.first_week_offset()).div_ceil(7)),
+            hidden_days: self.events.len() - drawn,
+        }
+    }
+}
+
+/// Walk a col x row grid clockwise from the first cell towards the centre.
+pub fn spiral_cells(col_num: usize, row_num: usize) -> Vec<(usize, usize)> {
+    let mut cells = Vec::with_capacity(col_num * row_num);
+    if col_num == 0 || row_num == 0 {
+        return cells;
+    }
+    let (mut left, mut right, mut top, mut bottom) = (0, col_num - 1, 0, row_num - 1);
+    loop {
+        for col in left..=right {
+            cells.push((col, top));
+        }
+        if top == bottom {
+            break;
+        }
+        top += 1;
+        for row in top..=bottom {
+            cells.push((right, row));
+        }
+        if left == right {
+            break;
+        }
+        right -= 1;
+        for col in (left..=right).rev() {
+            cells.push((col, bottom));
+        }
+        if top == bottom {
+            break;
+        }
+        bottom -= 1;
+        for row in (top..=bottom).rev() {
+            cells.push((left, row));
+        }
+        if left == right {
+            break;
+        }
+        left += 1;
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::testing;
+    use chrono::NaiveDate;
+    use proptest::prelude::*;
+
+    //Allowance for float error when comparing unit edges.
+    const EPSILON: f32 = 1e-3;
+
+    fn calendar(config: Config) -> Calendar {
+        Calendar::new(&config).unwrap()
+    }
+
+    fn default_config() -> Config {
+        Config {
+            birthdate: NaiveDate::from_ymd_opt(1990, 1, 1),
+            ..testing::config()
+        }
+    }
+
+    fn area(width: f32, height: f32) -> Rect {
+        Rect::from_min_size(pos2(10.0, 20.0), vec2(width, height))
+    }
+
+    fn layout(calendar: &Calendar, area: Rect) -> GridLayout {
+        calendar.grid_layout(area, area.size())
+    }
+
+    fn overlaps(a: &Rect, b: &Rect) -> bool {
+        a.min.x < b.max.x - EPSILON
+            && b.min.x < a.max.x - EPSILON
+            && a.min.y < b.max.y - EPSILON
+            && b.min.y < a.max.y - EPSILON
+    }
+
+    fn inside(unit: &Rect, area: &Rect) -> bool {
+        unit.min.x >= area.min.x - EPSILON
+            && unit.min.y >= area.min.y - EPSILON
+            && unit.max.x <= area.max.x + EPSILON
+            && unit.max.y <= area.max.y + EPSILON
+    }
+
+    #[test]
+    fn matrix_fits_every_week() {
+        let calendar = calendar(default_config());
+        for (width, height) in [
+            (1920.0, 1080.0),
+            (1080.0, 1920.0),
+            (300.0, 300.0),
+            (50.0, 2000.0),
+        ] {
+            let matrix = calendar.calculate_matrix(vec2(width, height));
+            assert!(matrix.col * matrix.row >= calendar.week_count());
+            assert!(matrix.unit_size > 0.0);
+        }
+    }
+
+    #[test]
+    fn matrix_of_empty_area_is_empty() {
+        let calendar = calendar(default_config());
+        for available in [vec2(0.0, 500.0), vec2(500.0, 0.0), vec2(-5.0, -5.0)] {
+            assert_eq!(calendar.calculate_matrix(available), Matrix::default());
+            assert!(layout(&calendar, area(available.x, available.y))
+                .units
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn first_unit_sits_inside_the_border() {
+        let config = default_config();
+        let config = Config {
+            grid_alignment: GridAlignment::Start,
+            ..config
+        };
+        let calendar = calendar(config.clone());
+        let area = area(1920.0, 1080.0);
+        let matrix = calendar.calculate_matrix(area.size());
+        let unit = calendar.calculate_pos(area, &matrix, (0, 0), 0);
+        let inset = (config.border_spacing + (1.0 - config.unit_ratio) / 2.0) * matrix.unit_size;
+        assert!((unit.min.x - (area.min.x + inset)).abs() < EPSILON);
+        assert!((unit.min.y - (area.min.y + inset)).abs() < EPSILON);
+        assert!((unit.width() - config.unit_ratio * matrix.unit_size).abs() < EPSILON);
+        assert!((unit.height() - config.unit_ratio * matrix.unit_size).abs() < EPSILON);
+    }
+
+    #[test]
+    fn flows_mirror_the_grid() {
+        let area = area(800.0, 600.0);
+        let left_to_right = layout(&calendar(default_config()), area);
+        let right_to_left = layout(
+            &calendar(Config {
+                horizontal_flow: HorizontalFlow::RightToLeft,
+                ..default_config()
+            }),
+            area,
+        );
+        let bottom_up = layout(
+            &calendar(Config {
+                vertical_flow: VerticalFlow::BottomUp,
+                ..default_config()
+            }),
+            area,
+        );
+        for ((unit, mirrored_x), mirrored_y) in left_to_right
+            .units
+            .iter()
+            .zip(&right_to_left.units)
+            .zip(&bottom_up.units)
+        {
+            assert!((unit.min.x - area.min.x - (area.max.x - mirrored_x.max.x)).abs() < EPSILON);
+            assert!((unit.min.y - mirrored_x.min.y).abs() < EPSILON);
+            assert!((unit.min.y - area.min.y - (area.max.y - mirrored_y.max.y)).abs() < EPSILON);
+            assert!((unit.min.x - mirrored_y.min.x).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn scrolling_layout_spans_the_width_and_holds_every_day() {
+        for week_orientation in [WeekOrientation::Horizontal, WeekOrientation::Vertical] {
+            let config = Config {
+                week_orientation,
+                ..default_config()
+            };
+            let layout = calendar(config.clone()).scrolling_layout(200.0, 2.0);
+            assert_eq!(layout.unit_size, 2.0);
+            assert_eq!(layout.units.len(), calendar(config.clone()).events.len());
+            let bottom = layout
+                .units
+                .iter()
+                .fold(0.0, |bottom: f32, unit| bottom.max(unit.max.y));
+            let area = Rect::from_min_size(pos2(0.0, 0.0), vec2(200.0, bottom));
+            assert!(layout.units.iter().all(|unit| inside(unit, &area)));
+
+            let mirrored = calendar(Config {
+                horizontal_flow: HorizontalFlow::RightToLeft,
+                ..config
+            })
+            .scrolling_layout(200.0, 2.0);
+            for (unit, mirrored) in layout.units.iter().zip(&mirrored.units) {
+                assert!((unit.min.x - (200.0 - mirrored.max.x)).abs() < EPSILON);
+                assert!((unit.min.y - mirrored.min.y).abs() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn fit_picks_the_largest_unit_over_both_sides() {
+        let calendar = calendar(default_config());
+        //Wide, tall and square areas, where a width only search left most of the space unused.
+        for (width, height) in [(3000.0, 200.0), (200.0, 3000.0), (900.0, 900.0)] {
+            let (cross_len, main_len) = calendar.axis_lengths(vec2(width, height));
+            let matrix = calendar.calculate_matrix(vec2(width, height));
+            let (grid_cross, grid_main) =
+                calendar.grid_extent(matrix.col, matrix.row, matrix.unit_size);
+            assert!(grid_cross <= cross_len + EPSILON && grid_main <= main_len + EPSILON);
+            //One side is filled, otherwise the units could grow.
+            assert!(
+                (grid_cross - cross_len).abs() < EPSILON || (grid_main - main_len).abs() < EPSILON
+            );
+            for col_num in [matrix.col - 1, matrix.col + 1]
+                .into_iter()
+                .filter(|&col| col > 0)
+            {
+                let row_num = calendar.week_count().div_ceil(col_num);
+                assert!(
+                    calendar.fitting_unit_size(cross_len, main_len, col_num, row_num)
+                        <= matrix.unit_size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_columns_are_kept() {
+        let calendar = calendar(Config {
+            grid_sizing: GridSizing::FixedColumns,
+            column_count: 10,
+            ..default_config()
+        });
+        let matrix = calendar.calculate_matrix(vec2(1920.0, 1080.0));
+        assert_eq!(matrix.col, 10);
+        assert_eq!(matrix.row, calendar.week_count().div_ceil(10));
+        let layout = layout(&calendar, area(1920.0, 1080.0));
+        assert_eq!(layout.units.len(), calendar.events.len());
+        assert_eq!(layout.metrics.hidden_days, 0);
+    }
+
+    #[test]
+    fn fixed_unit_size_hides_what_doesnt_fit() {
+        let calendar = calendar(Config {
+            grid_sizing: GridSizing::FixedUnitSize,
+            unit_size: 10.0,
+            ..default_config()
+        });
+        let area = area(400.0, 300.0);
+        let layout = layout(&calendar, area);
+        assert_eq!(layout.unit_size, 10.0);
+        assert!(layout.metrics.hidden_days > 0);
+        assert_eq!(
+            layout.units.len() + layout.metrics.hidden_days,
+            calendar.events.len()
+        );
+        assert!(layout.units.iter().all(|unit| inside(unit, &area)));
+        //A unit larger than the area leaves no grid at all.
+        let huge = Calendar::new(&Config {
+            unit_size: 200.0,
+            ..calendar.config.clone()
+        })
+        .unwrap();
+        assert!(huge.grid_layout(area, vec2(100.0, 100.0)).units.is_empty());
+    }
+
+    #[test]
+    fn alignment_places_the_leftover_space() {
+        let area = area(1000.0, 700.0);
+        let aligned = |grid_alignment| {
+            let calendar = calendar(Config {
+                grid_sizing: GridSizing::FixedColumns,
+                column_count: 3,
+                grid_alignment,
+                ..default_config()
+            });
+            let layout = layout(&calendar, area);
+            let left = layout
+                .units
+                .iter()
+                .map(|unit| unit.min.x)
+                .fold(f32::MAX, f32::min);
+            let right = layout
+                .units
+                .iter()
+                .map(|unit| unit.max.x)
+                .fold(f32::MIN, f32::max);
+            let inset = (calendar.config.border_spacing + (1.0 - calendar.config.unit_ratio) / 2.0)
+                * layout.unit_size;
+            (left - area.min.x - inset, area.max.x - right - inset)
+        };
+        //The units are capped by the height, leaving space on the sides.
+        let (start_left, start_right) = aligned(GridAlignment::Start);
+        assert!(start_left.abs() < EPSILON && start_right > 1.0);
+        let (center_left, center_right) = aligned(GridAlignment::Center);
+        assert!((center_left - center_right).abs() < EPSILON && center_left > 0.5);
+        let (justify_left, justify_right) = aligned(GridAlignment::Justify);
+        assert!(justify_left.abs() < EPSILON && justify_right.abs() < EPSILON);
+    }
+
+    #[test]
+    fn metrics_explain_the_fit() {
+        let calendar = calendar(default_config());
+        let area = area(1920.0, 1080.0);
+        let metrics = layout(&calendar, area).metrics;
+        assert!(metrics.fill_ratio > 0.0 && metrics.fill_ratio < 1.0);
+        assert!(metrics.wasted_area >= 0.0);
+        assert!(metrics.wasted_area < area.width() * area.height());
+        assert_eq!(metrics.hidden_days, 0);
+        let matrix = calendar.calculate_matrix(area.size());
+        assert!(metrics.empty_slots < matrix.row.max(matrix.col));
+        assert_eq!(
+            layout(&calendar, self::area(0.0, 0.0)).metrics.hidden_days,
+            calendar.events.len()
+        );
+    }
+
+    #[test]
+    fn spiral_visits_every_cell_once() {
+        for (col_num, row_num) in [(1, 1), (1, 5), (5, 1), (4, 4), (7, 3), (3, 8)] {
+            let mut cells = spiral_cells(col_num, row_num);
+            assert_eq!(cells.len(), col_num * row_num);
+            cells.sort();
+            cells.dedup();
+            assert_eq!(cells.len(), col_num * row_num);
+        }
+        assert!(spiral_cells(0, 4).is_empty());
+    }
+
+    fn arbitrary_config() -> impl Strategy<Value = Config> {
+        (
+            (0i64..40_000, 1i32..=120),
+            (0.1f32..=1.0, 0.0f32..=10.0, 0.0f32..=10.0, 0.0f32..=20.0),
+            (
+                prop::sample::select(WeekOrientation::ALL.to_vec()),
+                prop::sample::select(HorizontalFlow::ALL.to_vec()),
+                prop::sample::select(VerticalFlow::ALL.to_vec()),
+                prop::sample::select(FlowMode::ALL.to_vec()),
+                0u8..7,
+            ),
+            (
+                prop::sample::select(GridSizing::ALL.to_vec()),
+                prop::sample::select(GridAlignment::ALL.to_vec()),
+                1.0f32..=200.0,
+                1usize..=520,
+            ),
+        )
+            .prop_map(
+                |(
+                    (birth_offset, life_expectancy),
+                    (unit_ratio, col_spacing, row_spacing, border_spacing),
+                    (week_orientation, horizontal_flow, vertical_flow, flow_mode, week_start),
+                    (grid_sizing, grid_alignment, unit_size, column_count),
+                )| {
+                    Config {
+                        birthdate: NaiveDate::from_ymd_opt(1900, 1, 1)
+                            .map(|date| date + chrono::Duration::days(birth_offset)),
+                        life_expectancy,
+                        unit_ratio,
+                        col_spacing,
+                        row_spacing,
+                        border_spacing,
+                        week_orientation,
+                        horizontal_flow,
+                        vertical_flow,
+                        flow_mode,
+                        week_start: chrono::Weekday::try_from(week_start).unwrap(),
+                        grid_sizing,
+                        grid_alignment,
+                        unit_size,
+                        column_count,
+                        ..default_config()
+                    }
+                },
+            )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn every_shown_day_is_laid_out_once_inside_the_area(
+            config in arbitrary_config(),
+            width in 50.0f32..2560.0,
+            height in 50.0f32..2560.0,
+        ) {
+            let calendar = calendar(config);
+            let area = area(width, height);
+            let layout = layout(&calendar, area);
+            //Only a fixed unit size can leave days out.
+            if calendar.config.grid_sizing != GridSizing::FixedUnitSize {
+                prop_assert_eq!(layout.metrics.hidden_days, 0);
+            }
+            prop_assert_eq!(layout.units.len() + layout.metrics.hidden_days, calendar.events.len());
+            for unit in &layout.units {
+                prop_assert!(inside(unit, &area), "{:?} outside {:?}", unit, area);
+            }
+            //Sorted along x, only units starting before one ends can overlap it.
+            let mut units = layout.units.clone();
+            units.sort_by(|a, b| a.min.x.total_cmp(&b.min.x));
+            for (index, unit) in units.iter().enumerate() {
+                for other in units[index + 1..].iter().take_while(|other| other.min.x < unit.max.x) {
+                    prop_assert!(!overlaps(unit, other), "{:?} overlaps {:?}", unit, other);
+                }
+            }
+        }
+    }
+}
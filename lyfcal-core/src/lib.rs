@@ -0,0 +1,22 @@
+//! Core of lyfcal, a calendar of a whole life: the date model, lifespan computation, grid layout
+//! geometry and colour rules shared by the graphical, terminal and command line front ends.
+//!
+//! Nothing here depends on a UI toolkit. A front end loads a [`config::Config`], builds a
+//! [`calendar::Calendar`] from it, lays the days out with [`calendar::Calendar::grid_layout`] and
+//! paints each unit with the colours from [`calendar::Calendar::unit_fills`].
+
+pub mod calendar;
+pub mod clock;
+pub mod color;
+pub mod config;
+pub mod error;
+pub mod geometry;
+pub mod holiday;
+pub mod layout;
+pub mod recurrence;
+pub mod schedule;
+pub mod status;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+pub mod theme;
+pub mod vision;
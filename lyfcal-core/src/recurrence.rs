@@ -0,0 +1,564 @@
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Subset of an RFC 5545 RRULE: FREQ, INTERVAL, BYDAY, BYMONTHDAY, BYSETPOS, UNTIL and COUNT.
+/// Stored in its RRULE text form.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<ByDay>,
+    pub by_month_day: Vec<i32>,
+    pub by_set_pos: Vec<i32>,
+    pub until: Option<NaiveDate>,
+    pub count: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Weekday with an optional ordinal, i.e. "1MO" for the first Monday or "-1FR" for the last Friday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+impl RecurrenceRule {
+    pub fn new(freq: Frequency) -> Self {
+        Self {
+            freq,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_set_pos: Vec::new(),
+            until: None,
+            count: None,
+        }
+    }
+
+    /// Every occurrence from dtstart, stopping at the earliest of end, UNTIL and COUNT.
+    pub fn occurrences(&self, dtstart: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+        let end = self.until.map_or(end, |until| until.min(end));
+        let mut output = Vec::new();
+
+        for period in 0.. {
+            let Some((period_start, period_end)) = self.period(dtstart, period) else {
+                break;
+            };
+            if period_start > end {
+                break;
+            }
+
+            let mut candidates: Vec<NaiveDate> = period_start
+                .iter_days()
+                .take_while(|date| *date <= period_end)
+                .filter(|date| self.matches(*date, dtstart))
+                .collect();
+            if !self.by_set_pos.is_empty() {
+                candidates = select_set_pos(&candidates, &self.by_set_pos);
+            }
+
+            for date in candidates {
+                if date < dtstart {
+                    continue;
+                }
+                if date > end
+                    || self
+                        .count
+                        .is_some_and(|count| output.len() >= count as usize)
+                {
+                    return output;
+                }
+                output.push(date);
+            }
+        }
+        output
+    }
+
+    //First and last day of the nth period counted from the one containing dtstart.
+    fn period(&self, dtstart: NaiveDate, n: u32) -> Option<(NaiveDate, NaiveDate)> {
+        let step = n.checked_mul(self.interval.max(1))?;
+        match self.freq {
+            Frequency::Daily => {
+                let day = dtstart.checked_add_signed(Duration::days(step as i64))?;
+                Some((day, day))
+            }
+            Frequency::Weekly => {
+                let week_start = dtstart.week(Weekday::Mon).first_day();
+                let start = week_start.checked_add_signed(Duration::weeks(step as i64))?;
+                Some((start, start + Duration::days(6)))
+            }
+            Frequency::Monthly => {
+                let month_start = dtstart.with_day(1)?;
+                let start = month_start.checked_add_months(Months::new(step))?;
+                let end = start.checked_add_months(Months::new(1))?.pred_opt()?;
+                Some((start, end))
+            }
+            Frequency::Yearly => {
+                let start =
+                    NaiveDate::from_ymd_opt(dtstart.year().checked_add(step as i32)?, 1, 1)?;
+                let end = NaiveDate::from_ymd_opt(start.year(), 12, 31)?;
+                Some((start, end))
+            }
+        }
+    }
+
+    //Whether a date within a period is selected by the BYxxx parts, or by dtstart when none are given.
+    fn matches(&self, date: NaiveDate, dtstart: NaiveDate) -> bool {
+        if self.by_day.is_empty() && self.by_month_day.is_empty() {
+            return match self.freq {
+                Frequency::Daily => true,
+                Frequency::Weekly => date.weekday() == dtstart.weekday(),
+                Frequency::Monthly => date.day() == dtstart.day(),
+                Frequency::Yearly => date.month() == dtstart.month() && date.day() == dtstart.day(),
+            };
+        }
+        let month_day_match = self.by_month_day.is_empty()
+            || self.by_month_day.iter().any(|day| {
+                let days_in_month = days_in_month(date) as i32;
+                let day = if *day < 0 {
+                    days_in_month + day + 1
+                } else {
+                    *day
+                };
+                day == date.day() as i32
+            });
+        let weekday_match = self.by_day.is_empty()
+            || self.by_day.iter().any(|by_day| {
+                by_day.weekday == date.weekday()
+                    && by_day.ordinal.is_none_or(|ordinal| match self.freq {
+                        Frequency::Monthly => {
+                            weekday_ordinal(date.day(), days_in_month(date), ordinal)
+                        }
+                        Frequency::Yearly => {
+                            weekday_ordinal(date.ordinal(), days_in_year(date.year()), ordinal)
+                        }
+                        //Ordinals have no meaning for shorter periods.
+                        Frequency::Daily | Frequency::Weekly => true,
+                    })
+            });
+        month_day_match && weekday_match
+    }
+}
+
+//================================================== PARSING ==================================================
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let text = text.strip_prefix("RRULE:").unwrap_or(text);
+        let mut freq = None;
+        let mut rule = Self::new(Frequency::Daily);
+
+        for part in text.split(';').filter(|part| !part.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected KEY=VALUE, found \"{}\"", part))?;
+            let value = value.trim();
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("unsupported FREQ \"{}\"", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = parse_number(key, value)?;
+                    if rule.interval == 0 {
+                        return Err("INTERVAL must be at least 1".to_string());
+                    }
+                }
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = parse_list(key, value, 31)?;
+                }
+                "BYSETPOS" => {
+                    rule.by_set_pos = parse_list(key, value, 366)?;
+                }
+                "UNTIL" => {
+                    let date = value.get(..8).unwrap_or(value);
+                    rule.until = Some(
+                        NaiveDate::parse_from_str(date, "%Y%m%d")
+                            .map_err(|_| format!("UNTIL \"{}\" is not a YYYYMMDD date", value))?,
+                    );
+                }
+                "COUNT" => rule.count = Some(parse_number(key, value)?),
+                _ => return Err(format!("unsupported rule part \"{}\"", key)),
+            }
+        }
+
+        rule.freq = freq.ok_or_else(|| "FREQ is required".to_string())?;
+        if rule.until.is_some() && rule.count.is_some() {
+            return Err("UNTIL and COUNT cannot both be given".to_string());
+        }
+        //RFC 5545 leaves BYMONTHDAY out of weekly rules.
+        if rule.freq == Frequency::Weekly && !rule.by_month_day.is_empty() {
+            return Err("BYMONTHDAY cannot be used with FREQ=WEEKLY".to_string());
+        }
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let freq = match self.freq {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", freq)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|by_day| {
+                    let code = weekday_code(by_day.weekday);
+                    match by_day.ordinal {
+                        Some(ordinal) => format!("{}{}", ordinal, code),
+                        None => code.to_string(),
+                    }
+                })
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            write!(f, ";BYMONTHDAY={}", join_numbers(&self.by_month_day))?;
+        }
+        if !self.by_set_pos.is_empty() {
+            write!(f, ";BYSETPOS={}", join_numbers(&self.by_set_pos))?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for RecurrenceRule {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<RecurrenceRule> for String {
+    fn from(rule: RecurrenceRule) -> Self {
+        rule.to_string()
+    }
+}
+
+fn parse_number(key: &str, value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} \"{}\" is not a positive number", key, value))
+}
+
+//Comma separated non-zero numbers within ±limit.
+fn parse_list(key: &str, value: &str, limit: i32) -> Result<Vec<i32>, String> {
+    value
+        .split(',')
+        .map(|item| match item.trim().parse::<i32>() {
+            Ok(number) if number != 0 && number.abs() <= limit => Ok(number),
+            _ => Err(format!("{} value \"{}\" is out of range", key, item)),
+        })
+        .collect()
+}
+
+fn parse_by_day(item: &str) -> Result<ByDay, String> {
+    let item = item.trim().to_ascii_uppercase();
+    if item.len() < 2 {
+        return Err(format!("BYDAY value \"{}\" is not a weekday", item));
+    }
+    let (ordinal, code) = item.split_at(item.len() - 2);
+    let weekday = match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(format!("BYDAY value \"{}\" is not a weekday", item)),
+    };
+    let ordinal = match ordinal.trim_start_matches('+') {
+        "" => None,
+        number => match number.parse::<i32>() {
+            Ok(number) if number != 0 && number.abs() <= 53 => Some(number),
+            _ => return Err(format!("BYDAY ordinal \"{}\" is out of range", ordinal)),
+        },
+    };
+    Ok(ByDay { ordinal, weekday })
+}
+
+//================================================== MISC. FUNCTIONS ==================================================
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn join_numbers(numbers: &[i32]) -> String {
+    numbers
+        .iter()
+        .map(|number| number.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+//Whether the 1 based position of a day within a period of period_len days is the nth occurrence of its weekday.
+fn weekday_ordinal(position: u32, period_len: u32, ordinal: i32) -> bool {
+    if ordinal > 0 {
+        (position as i32 - 1) / 7 + 1 == ordinal
+    } else {
+        (period_len as i32 - position as i32) / 7 + 1 == -ordinal
+    }
+}
+
+//Pick 1 based positions (negative from the end) out of a period's sorted candidates.
+fn select_set_pos(candidates: &[NaiveDate], positions: &[i32]) -> Vec<NaiveDate> {
+    let len = candidates.len() as i32;
+    let mut output: Vec<NaiveDate> = positions
+        .iter()
+        .filter_map(|pos| {
+            let index = if *pos > 0 { pos - 1 } else { len + pos };
+            (0..len)
+                .contains(&index)
+                .then(|| candidates[index as usize])
+        })
+        .collect();
+    output.sort();
+    output.dedup();
+    output
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let next_month = date
+        .with_day(1)
+        .and_then(|first| first.checked_add_months(Months::new(1)));
+    match next_month {
+        Some(next_month) => next_month.pred_opt().map_or(31, |last| last.day()),
+        None => 31,
+    }
+}
+
+fn days_in_year(year: i32) -> u32 {
+    if NaiveDate::from_ymd_opt(year, 2, 29).is_some() {
+        366
+    } else {
+        365
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn occurrences(rule: &str, dtstart: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+        rule.parse::<RecurrenceRule>()
+            .unwrap()
+            .occurrences(dtstart, end)
+    }
+
+    #[test]
+    fn text_round_trips() {
+        for text in [
+            "FREQ=DAILY",
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR",
+            "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+            "FREQ=MONTHLY;BYDAY=2TU,-1FR",
+            "FREQ=MONTHLY;BYMONTHDAY=1,-1;COUNT=5",
+            "FREQ=YEARLY;UNTIL=20301231",
+        ] {
+            let rule: RecurrenceRule = text.parse().unwrap();
+            assert_eq!(rule.to_string(), text);
+            assert_eq!(rule.to_string().parse::<RecurrenceRule>(), Ok(rule));
+        }
+        //Prefix, case and a + ordinal are accepted but written in their plain form.
+        let rule: RecurrenceRule = "RRULE:freq=monthly;byday=+1mo".parse().unwrap();
+        assert_eq!(rule.to_string(), "FREQ=MONTHLY;BYDAY=1MO");
+    }
+
+    #[test]
+    fn last_working_day_of_the_month() {
+        assert_eq!(
+            occurrences(
+                "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+                date(2024, 1, 1),
+                date(2024, 12, 31),
+            ),
+            [
+                date(2024, 1, 31),
+                date(2024, 2, 29),
+                date(2024, 3, 29),
+                date(2024, 4, 30),
+                date(2024, 5, 31),
+                date(2024, 6, 28),
+                date(2024, 7, 31),
+                date(2024, 8, 30),
+                date(2024, 9, 30),
+                date(2024, 10, 31),
+                date(2024, 11, 29),
+                date(2024, 12, 31),
+            ]
+        );
+    }
+
+    #[test]
+    fn negative_month_day_counts_from_the_month_end() {
+        assert_eq!(
+            occurrences(
+                "FREQ=MONTHLY;BYMONTHDAY=-1",
+                date(2023, 12, 1),
+                date(2024, 4, 30)
+            ),
+            [
+                date(2023, 12, 31),
+                date(2024, 1, 31),
+                date(2024, 2, 29),
+                date(2024, 3, 31),
+                date(2024, 4, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn weekday_ordinals_pick_the_nth_from_either_end() {
+        assert_eq!(
+            occurrences(
+                "FREQ=MONTHLY;BYDAY=2TU",
+                date(2024, 1, 1),
+                date(2024, 3, 31)
+            ),
+            [date(2024, 1, 9), date(2024, 2, 13), date(2024, 3, 12)]
+        );
+        assert_eq!(
+            occurrences(
+                "FREQ=MONTHLY;BYDAY=-1FR",
+                date(2024, 1, 1),
+                date(2024, 3, 31)
+            ),
+            [date(2024, 1, 26), date(2024, 2, 23), date(2024, 3, 29)]
+        );
+        //Within a year the ordinal counts weeks of the whole year.
+        assert_eq!(
+            occurrences(
+                "FREQ=YEARLY;BYDAY=-1FR",
+                date(2024, 1, 1),
+                date(2025, 12, 31)
+            ),
+            [date(2024, 12, 27), date(2025, 12, 26)]
+        );
+    }
+
+    #[test]
+    fn weekly_interval_is_anchored_on_the_start_week() {
+        //Without BYDAY every other week falls on the weekday of the start.
+        assert_eq!(
+            occurrences(
+                "FREQ=WEEKLY;INTERVAL=2",
+                date(2024, 1, 3),
+                date(2024, 2, 15)
+            ),
+            [
+                date(2024, 1, 3),
+                date(2024, 1, 17),
+                date(2024, 1, 31),
+                date(2024, 2, 14)
+            ]
+        );
+        //Days of the start week before the start are skipped, the week still counts.
+        assert_eq!(
+            occurrences(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR",
+                date(2024, 1, 3),
+                date(2024, 1, 31),
+            ),
+            [
+                date(2024, 1, 5),
+                date(2024, 1, 15),
+                date(2024, 1, 19),
+                date(2024, 1, 29)
+            ]
+        );
+    }
+
+    #[test]
+    fn count_and_until_truncate() {
+        assert_eq!(
+            occurrences("FREQ=DAILY;COUNT=3", date(2024, 1, 1), date(2024, 12, 31)),
+            [date(2024, 1, 1), date(2024, 1, 2), date(2024, 1, 3)]
+        );
+        assert_eq!(
+            occurrences(
+                "FREQ=MONTHLY;UNTIL=20240315",
+                date(2024, 1, 15),
+                date(2024, 12, 31)
+            ),
+            [date(2024, 1, 15), date(2024, 2, 15), date(2024, 3, 15)]
+        );
+        //The earlier of UNTIL and the requested end wins.
+        assert_eq!(
+            occurrences(
+                "FREQ=YEARLY;UNTIL=20300101",
+                date(2024, 5, 1),
+                date(2025, 12, 31)
+            ),
+            [date(2024, 5, 1), date(2025, 5, 1)]
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        for text in [
+            "FREQ=DAILY;COUNT=3;UNTIL=20240101",
+            "FREQ=WEEKLY;BYMONTHDAY=1",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=MONTHLY;BYDAY=0MO",
+            "FREQ=MONTHLY;BYDAY=XX",
+            "FREQ=DAILY;UNTIL=tomorrow",
+        ] {
+            assert!(text.parse::<RecurrenceRule>().is_err(), "{}", text);
+        }
+    }
+}
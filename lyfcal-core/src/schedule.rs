@@ -0,0 +1,183 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Cyclic on/off shift pattern worked for a single employer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkSchedule {
+    pub name: String,
+    /// One entry per day of the cycle, true for an on-shift day.
+    pub pattern: Vec<bool>,
+    /// First day of the cycle, also the first day the schedule applies.
+    pub anchor: NaiveDate,
+    /// Last day the schedule applies, open ended if none.
+    pub until: Option<NaiveDate>,
+}
+
+impl WorkSchedule {
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        date >= self.anchor && self.until.is_none_or(|until| date <= until)
+    }
+
+    /// Whether the date is an on-shift day, None if the schedule doesn't cover it.
+    pub fn is_on_shift(&self, date: NaiveDate) -> Option<bool> {
+        if !self.covers(date) || self.pattern.is_empty() {
+            return None;
+        }
+        let day = (date - self.anchor).num_days() as usize;
+        Some(self.pattern[day % self.pattern.len()])
+    }
+}
+
+/// Shift state of a date given every configured schedule. Where schedules overlap the most recently anchored one applies.
+pub fn shift_state(schedules: &[WorkSchedule], date: NaiveDate) -> Option<bool> {
+    schedules
+        .iter()
+        .filter(|schedule| schedule.covers(date))
+        .max_by_key(|schedule| schedule.anchor)
+        .and_then(|schedule| schedule.is_on_shift(date))
+}
+
+/// Pattern written as a string of 1 (on) and 0 (off) days, whitespace is ignored.
+pub fn parse_pattern(text: &str) -> Result<Vec<bool>, String> {
+    let pattern = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '1' => Ok(true),
+            '0' => Ok(false),
+            _ => Err(format!("unexpected \"{}\", use 1 for on and 0 for off", c)),
+        })
+        .collect::<Result<Vec<bool>, String>>()?;
+    if pattern.is_empty() {
+        return Err("pattern is empty".to_string());
+    }
+    Ok(pattern)
+}
+
+pub fn format_pattern(pattern: &[bool]) -> String {
+    pattern
+        .iter()
+        .map(|on| if *on { '1' } else { '0' })
+        .collect()
+}
+
+//================================================== PRESETS ==================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShiftPreset {
+    FourOnFourOff,
+    PanAmerican,
+    TwentyFourFortyEight,
+    FiveOnTwoOff,
+}
+
+impl ShiftPreset {
+    pub const ALL: [Self; 4] = [
+        Self::FourOnFourOff,
+        Self::PanAmerican,
+        Self::TwentyFourFortyEight,
+        Self::FiveOnTwoOff,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::FourOnFourOff => "4 on / 4 off",
+            Self::PanAmerican => "pan american",
+            Self::TwentyFourFortyEight => "24 / 48",
+            Self::FiveOnTwoOff => "5 on / 2 off",
+        }
+    }
+
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            Self::FourOnFourOff => "11110000",
+            //2 on, 2 off, 3 on, 2 off, 2 on, 3 off.
+            Self::PanAmerican => "11001110011000",
+            Self::TwentyFourFortyEight => "100",
+            Self::FiveOnTwoOff => "1111100",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn schedule(pattern: &str, anchor: NaiveDate, until: Option<NaiveDate>) -> WorkSchedule {
+        WorkSchedule {
+            name: pattern.to_string(),
+            pattern: parse_pattern(pattern).unwrap(),
+            anchor,
+            until,
+        }
+    }
+
+    #[test]
+    fn pattern_repeats_from_the_anchor() {
+        let schedule = schedule("110", date(2024, 1, 30), None);
+        let states: Vec<Option<bool>> = (0..7)
+            .map(|day| schedule.is_on_shift(date(2024, 1, 30) + chrono::Days::new(day)))
+            .collect();
+        let on = Some(true);
+        let off = Some(false);
+        assert_eq!(states, [on, on, off, on, on, off, on]);
+        //Far into the future, across a leap day.
+        assert_eq!(
+            schedule.is_on_shift(date(2024, 1, 30) + chrono::Days::new(3000)),
+            on
+        );
+        assert_eq!(
+            schedule.is_on_shift(date(2024, 1, 30) + chrono::Days::new(3002)),
+            off
+        );
+        //The cycle doesn't run backwards from the anchor.
+        assert_eq!(schedule.is_on_shift(date(2024, 1, 29)), None);
+        assert_eq!(schedule.is_on_shift(date(1990, 1, 1)), None);
+    }
+
+    #[test]
+    fn until_is_the_last_day_covered() {
+        let schedule = schedule("1", date(2024, 1, 1), Some(date(2024, 3, 31)));
+        assert_eq!(schedule.is_on_shift(date(2024, 3, 31)), Some(true));
+        assert_eq!(schedule.is_on_shift(date(2024, 4, 1)), None);
+        assert_eq!(shift_state(&[schedule], date(2024, 4, 1)), None);
+    }
+
+    #[test]
+    fn latest_anchor_wins_where_schedules_overlap() {
+        let old = schedule("1", date(2020, 1, 1), None);
+        let new = schedule("0", date(2024, 1, 1), Some(date(2024, 12, 31)));
+        for schedules in [[old.clone(), new.clone()], [new, old]] {
+            assert_eq!(shift_state(&schedules, date(2023, 12, 31)), Some(true));
+            assert_eq!(shift_state(&schedules, date(2024, 6, 1)), Some(false));
+            //The older schedule applies again once the newer one ends.
+            assert_eq!(shift_state(&schedules, date(2025, 1, 1)), Some(true));
+            assert_eq!(shift_state(&schedules, date(2019, 12, 31)), None);
+        }
+    }
+
+    #[test]
+    fn patterns_are_ones_and_zeros() {
+        assert_eq!(
+            parse_pattern(" 11 0\t1\n"),
+            Ok(vec![true, true, false, true])
+        );
+        for preset in ShiftPreset::ALL {
+            assert_eq!(
+                format_pattern(&parse_pattern(preset.pattern()).unwrap()),
+                preset.pattern()
+            );
+        }
+        assert_eq!(parse_pattern(""), Err("pattern is empty".to_string()));
+        assert_eq!(parse_pattern("  "), Err("pattern is empty".to_string()));
+        assert_eq!(
+            parse_pattern("1102"),
+            Err("unexpected \"2\", use 1 for on and 0 for off".to_string())
+        );
+        assert!(parse_pattern("on off").is_err());
+    }
+}
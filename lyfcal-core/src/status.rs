@@ -0,0 +1,258 @@
+//! Progress through the lifespan summarised for status bars and shell prompts.
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::calendar::Calendar;
+use super::schedule::shift_state;
+
+const BAR_WIDTH: usize = 10;
+
+/// Every this many days lived is a milestone.
+pub const DAY_MILESTONE: usize = 1000;
+
+//================================================== STATUS ==================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Status {
+    pub days_lived: usize,
+    pub days_total: usize,
+    pub next_milestone: Option<Milestone>,
+    /// Names of the holidays, highlights, notes and shifts falling on the elapsed date.
+    pub events_today: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Milestone {
+    pub name: String,
+    pub date: NaiveDate,
+    pub days_until: i64,
+}
+
+impl Status {
+    /// Progress up to the elapsed date of populated day data.
+    pub fn new(calendar: &Calendar) -> Self {
+        let config = &calendar.config;
+        let today = calendar.elapsed_date();
+        let days_lived = calendar.events.range(..=today).count();
+
+        let mut events_today = Vec::new();
+        if let Some(name) = calendar.holidays.get(&today) {
+            events_today.push(name.to_string());
+        }
+        if let Some(highlight) = calendar
+            .highlighted_days
+            .get(&today)
+            .and_then(|index| config.highlights.get(*index))
+        {
+            events_today.push(highlight.name.clone());
+        }
+        if config.is_birthday(today) {
+            events_today.push("birthday".to_string());
+        }
+        if shift_state(&config.work_schedules, today) == Some(true) {
+            events_today.push("on shift".to_string());
+        }
+        if let Some(note) = config.notes.get(&today) {
+            events_today.push(note.clone());
+        }
+
+        Self {
+            days_lived,
+            days_total: calendar.events.len(),
+            next_milestone: next_milestone(calendar, today, days_lived),
+            events_today,
+        }
+    }
+
+    pub fn days_remaining(&self) -> usize {
+        self.days_total - self.days_lived
+    }
+
+    pub fn percent_lived(&self) -> f64 {
+        if self.days_total == 0 {
+            return 0.0;
+        }
+        self.days_lived as f64 / self.days_total as f64 * 100.0
+    }
+
+    pub fn bar(&self) -> String {
+        let filled =
+            ((self.percent_lived() / 100.0 * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+        format!("{}{}", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled))
+    }
+
+    /// Circle filling up in quarters.
+    pub fn glyph(&self) -> char {
+        const GLYPHS: [char; 5] = ['○', '◔', '◑', '◕', '●'];
+        GLYPHS[((self.percent_lived() / 25.0).round() as usize).min(4)]
+    }
+
+    /// Fill in {percent}, {lived}, {remaining}, {bar}, {glyph}, {milestone} and {events}.
+    pub fn render(&self, template: &str) -> String {
+        let milestone = match &self.next_milestone {
+            Some(milestone) => format!("{} in {} days", milestone.name, milestone.days_until),
+            None => String::new(),
+        };
+        template
+            .replace("{percent}", &format!("{:.1}", self.percent_lived()))
+            .replace("{lived}", &self.days_lived.to_string())
+            .replace("{remaining}", &self.days_remaining().to_string())
+            .replace("{bar}", &self.bar())
+            .replace("{glyph}", &self.glyph().to_string())
+            .replace("{milestone}", &milestone)
+            .replace("{events}", &self.events_today.join(", "))
+    }
+
+    /// Waybar custom module object, with the details in the tooltip.
+    pub fn waybar_json(&self, template: &str) -> String {
+        let mut tooltip = vec![format!(
+            "{} days lived, {} to go",
+            self.days_lived,
+            self.days_remaining()
+        )];
+        if let Some(milestone) = &self.next_milestone {
+            tooltip.push(format!(
+                "next: {} on {} ({} days)",
+                milestone.name, milestone.date, milestone.days_until
+            ));
+        }
+        if !self.events_today.is_empty() {
+            tooltip.push(format!("today: {}", self.events_today.join(", ")));
+        }
+        serde_json::json!({
+            "text": self.render(template),
+            "tooltip": tooltip.join("\n"),
+            "class": "lyfcal",
+            "percentage": self.percent_lived().round() as u8,
+        })
+        .to_string()
+    }
+}
+
+//Soonest of the next birthday, the next round number of days lived and retirement.
+fn next_milestone(calendar: &Calendar, today: NaiveDate, days_lived: usize) -> Option<Milestone> {
+    let config = &calendar.config;
+    let birthdate = config.birthdate?;
+    let last_day = *calendar.events.last_key_value()?.0;
+    let mut milestones = Vec::new();
+
+    let age = (0..=1).find_map(|extra| {
+        let years = (today.year() - birthdate.year() + extra).max(1);
+        let birthday = config.birthday_at_age(years)?;
+        (birthday > today).then_some((years, birthday))
+    });
+    if let Some((years, birthday)) = age {
+        milestones.push((format!("age {}", years), birthday));
+    }
+
+    let next_days = (days_lived / DAY_MILESTONE + 1) * DAY_MILESTONE;
+    if let Some((date, _)) = calendar.events.iter().nth(next_days - 1) {
+        milestones.push((format!("{} days", next_days), *date));
+    }
+
+    if let Some(retirement) = config
+        .birthday_at_age(config.retirement_age)
+        .filter(|retirement| *retirement > today)
+    {
+        milestones.push(("retirement".to_string(), retirement));
+    }
+
+    milestones
+        .into_iter()
+        .filter(|(_, date)| *date <= last_day)
+        .min_by_key(|(_, date)| *date)
+        .map(|(name, date)| Milestone {
+            name,
+            date,
+            days_until: (date - today).num_days(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, LeapDayPolicy};
+    use crate::testing;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn leap_day_birthday_milestone_follows_policy() {
+        for (policy, birthday) in [
+            (LeapDayPolicy::February28, date(2100, 2, 28)),
+            (LeapDayPolicy::March1, date(2100, 3, 1)),
+        ] {
+            let config = Config {
+                birthdate: Some(date(2000, 2, 29)),
+                leap_day_policy: policy,
+                elapsed_date_bool: false,
+                elapsed_date: date(2100, 2, 27),
+                life_expectancy: 110,
+                retirement_age: 67,
+                ..testing::config()
+            };
+            let status = Status::new(&Calendar::new(&config).unwrap());
+            let milestone = status.next_milestone.unwrap();
+            assert_eq!(milestone.name, "age 100");
+            assert_eq!(milestone.date, birthday);
+
+            let calendar = Calendar::new(&Config {
+                elapsed_date: birthday,
+                ..config
+            })
+            .unwrap();
+            assert!(Status::new(&calendar)
+                .events_today
+                .contains(&"birthday".to_string()));
+        }
+    }
+
+    fn status() -> Status {
+        Status {
+            days_lived: 250,
+            days_total: 1000,
+            next_milestone: Some(Milestone {
+                name: "age 30".to_string(),
+                date: date(2024, 6, 11),
+                days_until: 10,
+            }),
+            events_today: vec!["on shift".to_string(), "say \"hi\"\tto Ann".to_string()],
+        }
+    }
+
+    #[test]
+    fn template_fields_are_filled_in() {
+        assert_eq!(
+            status().render("{glyph} {bar} {percent}% {lived}/{remaining} {milestone} [{events}]"),
+            "◔ ███░░░░░░░ 25.0% 250/750 age 30 in 10 days [on shift, say \"hi\"\tto Ann]"
+        );
+        let status = Status {
+            next_milestone: None,
+            events_today: Vec::new(),
+            ..status()
+        };
+        assert_eq!(
+            status.render("{milestone}|{events}|{unknown}"),
+            "||{unknown}"
+        );
+    }
+
+    #[test]
+    fn waybar_output_is_one_escaped_json_line() {
+        let line = status().waybar_json("{percent}% \"{events}\"");
+        assert!(!line.contains('\n'));
+        assert!(line.contains(r#"say \"hi\"\tto Ann"#));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&line).unwrap(),
+            serde_json::json!({
+                "text": "25.0% \"on shift, say \"hi\"\tto Ann\"",
+                "tooltip": "250 days lived, 750 to go\nnext: age 30 on 2024-06-11 (10 days)\ntoday: on shift, say \"hi\"\tto Ann",
+                "class": "lyfcal",
+                "percentage": 25,
+            })
+        );
+    }
+}
@@ -0,0 +1,16 @@
+//! Fixtures shared by the tests of the core and of the front ends, behind the `test-util` feature.
+
+use chrono::{Local, TimeZone};
+
+use super::clock::FixedClock;
+use super::config::Config;
+
+/// Clock stopped at noon on the 1st of June 2024.
+pub fn clock() -> FixedClock {
+    FixedClock(Local.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap())
+}
+
+/// Default config as created at [`clock`], with the default birthdate of the 1st of January 2000.
+pub fn config() -> Config {
+    Config::new(&clock())
+}
@@ -0,0 +1,476 @@
+//! Named colour schemes bundling every grid colour with the look of the config window.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::color::Color;
+use super::config::{save_json, Config};
+use super::error::LyfcalError;
+
+/// Every colour of the grid, each projected colour paired with its elapsed one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    pub weekday: Color,
+    pub weekday_elapsed: Color,
+    pub weekend: Color,
+    pub weekend_elapsed: Color,
+    pub holiday: Color,
+    pub holiday_elapsed: Color,
+    pub on_shift: Color,
+    pub on_shift_elapsed: Color,
+    pub off_shift: Color,
+    pub off_shift_elapsed: Color,
+    pub birthday: Color,
+    pub birthday_elapsed: Color,
+    pub today: Color,
+}
+
+/// Look of the config window, which front ends map onto their toolkit's styling.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowVisuals {
+    /// Whether widgets start from the toolkit's dark or light style.
+    pub dark_mode: bool,
+    pub background: Color,
+    pub text: Color,
+    /// Selections and links.
+    pub accent: Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub palette: Palette,
+    pub window: WindowVisuals,
+}
+
+impl Palette {
+    /// Colours currently set in a config.
+    pub fn of(config: &Config) -> Self {
+        Self {
+            weekday: config.color_weekday,
+            weekday_elapsed: config.color_weekday_elapsed,
+            weekend: config.color_weekend,
+            weekend_elapsed: config.color_weekend_elapsed,
+            holiday: config.color_holiday,
+            holiday_elapsed: config.color_holiday_elapsed,
+            on_shift: config.color_on_shift,
+            on_shift_elapsed: config.color_on_shift_elapsed,
+            off_shift: config.color_off_shift,
+            off_shift_elapsed: config.color_off_shift_elapsed,
+            birthday: config.color_birthday,
+            birthday_elapsed: config.color_birthday_elapsed,
+            today: config.color_today,
+        }
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        config.color_weekday = self.weekday;
+        config.color_weekday_elapsed = self.weekday_elapsed;
+        config.color_weekend = self.weekend;
+        config.color_weekend_elapsed = self.weekend_elapsed;
+        config.color_holiday = self.holiday;
+        config.color_holiday_elapsed = self.holiday_elapsed;
+        config.color_on_shift = self.on_shift;
+        config.color_on_shift_elapsed = self.on_shift_elapsed;
+        config.color_off_shift = self.off_shift;
+        config.color_off_shift_elapsed = self.off_shift_elapsed;
+        config.color_birthday = self.birthday;
+        config.color_birthday_elapsed = self.birthday_elapsed;
+        config.color_today = self.today;
+    }
+
+    /// Each projected colour with its elapsed one, named as in the config window.
+    pub fn pairs(&self) -> [(&'static str, Color, Color); 6] {
+        [
+            ("weekday", self.weekday, self.weekday_elapsed),
+            ("weekend", self.weekend, self.weekend_elapsed),
+            ("holiday", self.holiday, self.holiday_elapsed),
+            ("on shift", self.on_shift, self.on_shift_elapsed),
+            ("off shift", self.off_shift, self.off_shift_elapsed),
+            ("birthday", self.birthday, self.birthday_elapsed),
+        ]
+    }
+
+    /// Palette with every colour passed through a function.
+    pub fn map(&self, f: impl Fn(Color) -> Color) -> Self {
+        Self {
+            weekday: f(self.weekday),
+            weekday_elapsed: f(self.weekday_elapsed),
+            weekend: f(self.weekend),
+            weekend_elapsed: f(self.weekend_elapsed),
+            holiday: f(self.holiday),
+            holiday_elapsed: f(self.holiday_elapsed),
+            on_shift: f(self.on_shift),
+            on_shift_elapsed: f(self.on_shift_elapsed),
+            off_shift: f(self.off_shift),
+            off_shift_elapsed: f(self.off_shift_elapsed),
+            birthday: f(self.birthday),
+            birthday_elapsed: f(self.birthday_elapsed),
+            today: f(self.today),
+        }
+    }
+
+    //Palette with every projected colour at one opacity and every elapsed colour at another.
+    fn with_alphas(
+        [weekday, weekend, holiday, on_shift, off_shift, birthday, today]: [[u8; 3]; 7],
+        projected_alpha: u8,
+        elapsed_alpha: u8,
+        today_alpha: u8,
+    ) -> Self {
+        let color = |[r, g, b]: [u8; 3], a| Color::from_rgba_unmultiplied(r, g, b, a);
+        Self {
+            weekday: color(weekday, projected_alpha),
+            weekday_elapsed: color(weekday, elapsed_alpha),
+            weekend: color(weekend, projected_alpha),
+            weekend_elapsed: color(weekend, elapsed_alpha),
+            holiday: color(holiday, projected_alpha),
+            holiday_elapsed: color(holiday, elapsed_alpha),
+            on_shift: color(on_shift, projected_alpha),
+            on_shift_elapsed: color(on_shift, elapsed_alpha),
+            off_shift: color(off_shift, projected_alpha),
+            off_shift_elapsed: color(off_shift, elapsed_alpha),
+            birthday: color(birthday, projected_alpha),
+            birthday_elapsed: color(birthday, elapsed_alpha),
+            today: color(today, today_alpha),
+        }
+    }
+}
+
+impl Theme {
+    /// Themes shipped with lyfcal, the first being the default.
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::dark(),
+            Self::light(),
+            Self::high_contrast(),
+            Self::solarized(),
+            Self::paper(),
+            Self::colour_blind_safe_dark(),
+            Self::colour_blind_safe_light(),
+        ]
+    }
+
+    /// Translucent white units over the desktop, the default look.
+    pub fn dark() -> Self {
+        Self {
+            name: "dark".to_string(),
+            palette: Palette::with_alphas(
+                [
+                    [255, 255, 255],
+                    [225, 225, 250],
+                    [250, 200, 80],
+                    [120, 170, 255],
+                    [225, 225, 250],
+                    [255, 47, 47],
+                    [255, 255, 255],
+                ],
+                127,
+                15,
+                127,
+            ),
+            window: WindowVisuals {
+                dark_mode: true,
+                background: Color::from_rgb(27, 27, 27),
+                text: Color::from_rgb(140, 140, 140),
+                accent: Color::from_rgb(0, 92, 128),
+            },
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name: "light".to_string(),
+            palette: Palette::with_alphas(
+                [
+                    [40, 40, 40],
+                    [60, 60, 110],
+                    [200, 130, 0],
+                    [30, 90, 200],
+                    [60, 60, 110],
+                    [210, 30, 30],
+                    [0, 0, 0],
+                ],
+                140,
+                25,
+                170,
+            ),
+            window: WindowVisuals {
+                dark_mode: false,
+                background: Color::from_rgb(248, 248, 248),
+                text: Color::from_rgb(60, 60, 60),
+                accent: Color::from_rgb(144, 209, 255),
+            },
+        }
+    }
+
+    /// Opaque, saturated units with elapsed days dimmed rather than faded out.
+    pub fn high_contrast() -> Self {
+        let opaque = |[r, g, b]: [u8; 3]| Color::from_rgb(r, g, b);
+        let dimmed = |[r, g, b]: [u8; 3]| Color::from_rgb(r / 3, g / 3, b / 3);
+        let [weekday, weekend, holiday, on_shift, birthday, today] = [
+            [255, 255, 255],
+            [0, 255, 255],
+            [255, 255, 0],
+            [0, 160, 255],
+            [255, 0, 255],
+            [255, 200, 0],
+        ];
+        Self {
+            name: "high contrast".to_string(),
+            palette: Palette {
+                weekday: opaque(weekday),
+                weekday_elapsed: dimmed(weekday),
+                weekend: opaque(weekend),
+                weekend_elapsed: dimmed(weekend),
+                holiday: opaque(holiday),
+                holiday_elapsed: dimmed(holiday),
+                on_shift: opaque(on_shift),
+                on_shift_elapsed: dimmed(on_shift),
+                off_shift: opaque(weekend),
+                off_shift_elapsed: dimmed(weekend),
+                birthday: opaque(birthday),
+                birthday_elapsed: dimmed(birthday),
+                today: opaque(today),
+            },
+            window: WindowVisuals {
+                dark_mode: true,
+                background: Color::from_rgb(0, 0, 0),
+                text: Color::from_rgb(255, 255, 255),
+                accent: Color::from_rgb(0, 90, 255),
+            },
+        }
+    }
+
+    /// Ethan Schoonover's solarized accents over its darkest base.
+    pub fn solarized() -> Self {
+        Self {
+            name: "solarized".to_string(),
+            palette: Palette::with_alphas(
+                [
+                    [147, 161, 161],
+                    [108, 113, 196],
+                    [181, 137, 0],
+                    [38, 139, 210],
+                    [42, 161, 152],
+                    [220, 50, 47],
+                    [203, 75, 22],
+                ],
+                191,
+                31,
+                191,
+            ),
+            window: WindowVisuals {
+                dark_mode: true,
+                background: Color::from_rgb(0, 43, 54),
+                text: Color::from_rgb(131, 148, 150),
+                accent: Color::from_rgb(38, 139, 210),
+            },
+        }
+    }
+
+    /// Muted inks on warm paper.
+    pub fn paper() -> Self {
+        Self {
+            name: "paper".to_string(),
+            palette: Palette::with_alphas(
+                [
+                    [60, 50, 40],
+                    [110, 90, 70],
+                    [190, 120, 40],
+                    [70, 100, 140],
+                    [110, 90, 70],
+                    [170, 50, 40],
+                    [60, 50, 40],
+                ],
+                160,
+                25,
+                200,
+            ),
+            window: WindowVisuals {
+                dark_mode: false,
+                background: Color::from_rgb(245, 240, 225),
+                text: Color::from_rgb(60, 50, 40),
+                accent: Color::from_rgb(220, 190, 140),
+            },
+        }
+    }
+
+    /// Okabe and Ito's palette, whose hues stay apart under every common colour vision deficiency,
+    /// with birthdays set apart from weekdays by brightness as well as hue.
+    pub fn colour_blind_safe_dark() -> Self {
+        Self {
+            name: "colour blind safe dark".to_string(),
+            palette: Palette::with_alphas(
+                [
+                    [255, 255, 255],
+                    [86, 180, 233],
+                    [240, 228, 66],
+                    [0, 158, 115],
+                    [86, 180, 233],
+                    [230, 159, 0],
+                    [240, 228, 66],
+                ],
+                191,
+                25,
+                191,
+            ),
+            window: Self::dark().window,
+        }
+    }
+
+    pub fn colour_blind_safe_light() -> Self {
+        Self {
+            name: "colour blind safe light".to_string(),
+            palette: Palette::with_alphas(
+                [
+                    [40, 40, 40],
+                    [0, 114, 178],
+                    [230, 159, 0],
+                    [0, 158, 115],
+                    [0, 114, 178],
+                    [213, 94, 0],
+                    [0, 0, 0],
+                ],
+                191,
+                30,
+                200,
+            ),
+            window: Self::light().window,
+        }
+    }
+
+    /// Theme capturing the colours and window look currently set in a config.
+    pub fn from_config(name: &str, config: &Config) -> Self {
+        Self {
+            name: name.to_string(),
+            palette: Palette::of(config),
+            window: config.window_visuals,
+        }
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        self.palette.apply(config);
+        config.window_visuals = self.window;
+    }
+
+    /// Whether the config is set to exactly this theme's colours.
+    pub fn is_applied(&self, config: &Config) -> bool {
+        Palette::of(config) == self.palette && config.window_visuals == self.window
+    }
+
+    /// File name the theme is exported under, its name made safe for any file system.
+    pub fn file_name(&self) -> String {
+        let stem: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        format!("{}.json", stem.trim_matches('-'))
+    }
+
+    /// File name the theme is saved under among the user themes. Names leaving nothing of the file name are
+    /// rejected, and so are those of built in themes, which the saved file would replace.
+    pub fn export_file_name(&self) -> Result<String, LyfcalError> {
+        let file_name = self.file_name();
+        let problem = if file_name == ".json" {
+            "has no letters or digits"
+        } else if Self::builtin()
+            .iter()
+            .any(|theme| theme.file_name() == file_name)
+        {
+            "is taken by a built in theme"
+        } else {
+            return Ok(file_name);
+        };
+        Err(LyfcalError::ThemeName {
+            name: self.name.clone(),
+            problem,
+        })
+    }
+
+    /// Theme from the contents of a theme file, the path only naming it in errors.
+    pub fn parse(bytes: &[u8], path: &Path) -> Result<Self, LyfcalError> {
+        serde_json::from_slice(bytes).map_err(|error| LyfcalError::Parse {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, LyfcalError> {
+        let bytes = std::fs::read(path).map_err(|error| LyfcalError::Io {
+            action: "read",
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        })?;
+        Self::parse(&bytes, path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), LyfcalError> {
+        save_json(self, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::config;
+
+    #[test]
+    fn default_config_uses_the_first_builtin_theme() {
+        assert!(Theme::builtin()[0].is_applied(&config()));
+    }
+
+    #[test]
+    fn applied_theme_is_captured_back_from_the_config() {
+        for theme in Theme::builtin() {
+            let mut config = config();
+            theme.apply(&mut config);
+            assert!(theme.is_applied(&config));
+            assert_eq!(Theme::from_config(&theme.name, &config), theme);
+        }
+    }
+
+    #[test]
+    fn theme_file_round_trips() {
+        let theme = Theme::solarized();
+        let path = std::env::temp_dir()
+            .join(format!("lyfcal-theme-test-{}", std::process::id()))
+            .join(theme.file_name());
+        theme.save(&path).unwrap();
+        assert_eq!(Theme::load(&path).unwrap(), theme);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert!(matches!(
+            Theme::parse(b"{\"name\": 1}", &path),
+            Err(LyfcalError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn export_needs_a_name_of_its_own() {
+        let export = |name: &str| Theme::from_config(name, &config()).export_file_name();
+        assert_eq!(export("My Theme!"), Ok("my-theme.json".to_string()));
+        for name in ["", "   ", "!?", "-- * --"] {
+            assert!(
+                matches!(export(name), Err(LyfcalError::ThemeName { .. })),
+                "{:?}",
+                name
+            );
+        }
+        for theme in Theme::builtin() {
+            assert!(matches!(
+                export(&theme.name),
+                Err(LyfcalError::ThemeName { .. })
+            ));
+            assert!(matches!(
+                export(&theme.name.to_uppercase()),
+                Err(LyfcalError::ThemeName { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! How the grid's colours are perceived with colour vision deficiencies, and which of them can't be told apart.
+
+use serde::{Deserialize, Serialize};
+
+use super::color::{gamma_from_linear, linear_from_gamma, Color};
+use super::config::Config;
+use super::theme::Palette;
+
+/// Smallest CIE76 colour difference between an elapsed and projected colour that reads at a glance
+/// on units a few points wide, well above the just noticeable difference of about 2.3.
+pub const MIN_DIFFERENCE: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorVision {
+    Typical,
+    /// No red cones.
+    Protanopia,
+    /// No green cones, the most common deficiency.
+    Deuteranopia,
+    /// No blue cones.
+    Tritanopia,
+}
+
+/// An elapsed and projected colour that look alike to someone with the given colour vision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContrastWarning {
+    /// Kind of day the colours are for, i.e. "birthday".
+    pub name: &'static str,
+    pub vision: ColorVision,
+    pub difference: f32,
+}
+
+impl ColorVision {
+    pub const ALL: [Self; 4] = [
+        Self::Typical,
+        Self::Protanopia,
+        Self::Deuteranopia,
+        Self::Tritanopia,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Typical => "typical",
+            Self::Protanopia => "protanopia",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Tritanopia => "tritanopia",
+        }
+    }
+
+    //Machado, Oliveira and Fernandes' 2009 model at full severity, applied to linear RGB.
+    fn matrix(&self) -> Option<[[f32; 3]; 3]> {
+        match self {
+            Self::Typical => None,
+            Self::Protanopia => Some([
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ]),
+            Self::Deuteranopia => Some([
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ]),
+            Self::Tritanopia => Some([
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ]),
+        }
+    }
+
+    /// The colour as it is perceived with this colour vision, keeping its opacity.
+    pub fn simulate(&self, color: Color) -> Color {
+        let Some(matrix) = self.matrix() else {
+            return color;
+        };
+        if color.a() == 0 {
+            return color;
+        }
+        let alpha = color.a() as f32 / 255.0;
+        let rgb = linear_rgb(color);
+        let [r, g, b] = matrix.map(|row| {
+            //Premultiplied again after the transform, the way the colour was stored.
+            let linear = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2];
+            gamma_from_linear(linear.clamp(0.0, 1.0) * alpha)
+        });
+        Color::from_rgba_premultiplied(r, g, b, color.a())
+    }
+
+    /// Every colour of a palette as perceived with this colour vision.
+    pub fn simulate_palette(&self, palette: &Palette) -> Palette {
+        palette.map(|color| self.simulate(color))
+    }
+}
+
+/// Perceived difference between two colours composed over a background.
+pub fn difference(a: Color, b: Color, background: Color, vision: ColorVision) -> f32 {
+    let [l1, a1, b1] = lab(vision.simulate(a.over(background)));
+    let [l2, a2, b2] = lab(vision.simulate(b.over(background)));
+    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+}
+
+/// Elapsed and projected colours of the config that can't be told apart over the config window's
+/// background, with typical colour vision or any of the simulated deficiencies.
+pub fn contrast_warnings(config: &Config) -> Vec<ContrastWarning> {
+    let background = config.window_visuals.background;
+    Palette::of(config)
+        .pairs()
+        .into_iter()
+        .flat_map(|(name, projected, elapsed)| {
+            ColorVision::ALL.into_iter().filter_map(move |vision| {
+                let difference = difference(projected, elapsed, background, vision);
+                (difference < MIN_DIFFERENCE).then_some(ContrastWarning {
+                    name,
+                    vision,
+                    difference,
+                })
+            })
+        })
+        .collect()
+}
+
+//Unmultiplied linear RGB of a colour, black for a fully transparent one.
+fn linear_rgb(color: Color) -> [f32; 3] {
+    if color.a() == 0 {
+        return [0.0; 3];
+    }
+    let alpha = color.a() as f32 / 255.0;
+    [color.r(), color.g(), color.b()].map(|channel| linear_from_gamma(channel) / alpha)
+}
+
+//CIE L*a*b* of an opaque colour under a D65 white point.
+fn lab(color: Color) -> [f32; 3] {
+    let [r, g, b] = linear_rgb(color);
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let f = |t: f32| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::config;
+    use crate::theme::Theme;
+
+    #[test]
+    fn greys_look_the_same_with_every_colour_vision() {
+        for grey in [0, 27, 128, 255] {
+            let color = Color::from_rgb(grey, grey, grey);
+            for vision in ColorVision::ALL {
+                let simulated = vision.simulate(color).to_array();
+                assert!(simulated
+                    .iter()
+                    .zip(color.to_array())
+                    .all(|(a, b)| a.abs_diff(b) <= 1));
+            }
+        }
+    }
+
+    #[test]
+    fn red_and_green_merge_without_green_cones() {
+        let background = Color::from_rgb(27, 27, 27);
+        let (red, green) = (Color::from_rgb(200, 90, 60), Color::from_rgb(115, 130, 50));
+        assert!(difference(red, green, background, ColorVision::Typical) > MIN_DIFFERENCE);
+        assert!(difference(red, green, background, ColorVision::Deuteranopia) < MIN_DIFFERENCE);
+    }
+
+    #[test]
+    fn builtin_themes_keep_elapsed_days_apart() {
+        for theme in Theme::builtin() {
+            let mut config = config();
+            theme.apply(&mut config);
+            assert_eq!(contrast_warnings(&config), Vec::new(), "{}", theme.name);
+        }
+    }
+
+    #[test]
+    fn alike_pair_is_warned_about() {
+        let config = Config {
+            color_birthday_elapsed: config().color_birthday,
+            ..config()
+        };
+        let warnings = contrast_warnings(&config);
+        assert_eq!(warnings.len(), ColorVision::ALL.len());
+        assert!(warnings.iter().all(|warning| warning.name == "birthday"));
+    }
+
+    #[test]
+    fn transparent_colours_over_a_transparent_background_are_warned_about() {
+        assert_eq!(
+            difference(
+                Color::TRANSPARENT,
+                Color::TRANSPARENT,
+                Color::TRANSPARENT,
+                ColorVision::Typical
+            ),
+            0.0
+        );
+        let mut config = config();
+        config.window_visuals.background = Color::TRANSPARENT;
+        config.color_birthday = Color::TRANSPARENT;
+        config.color_birthday_elapsed = Color::TRANSPARENT;
+        let birthday_warnings = contrast_warnings(&config)
+            .into_iter()
+            .filter(|warning| warning.name == "birthday")
+            .count();
+        assert_eq!(birthday_warnings, ColorVision::ALL.len());
+    }
+}
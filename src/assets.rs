@@ -0,0 +1,140 @@
+use lyfcal_core::error::LyfcalError;
+use lyfcal_core::theme::Theme;
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub const ICON: &str = "icon.png";
+//Theme files, built in ones named after their theme.
+pub const THEME_DIR: &str = "themes";
+
+//Every asset compiled into the binary, by its path within the asset directory. The built in themes
+//are defined by lyfcal-core and served as theme files, so a user theme of the same file name replaces one.
+fn embedded_assets() -> &'static [(String, Cow<'static, [u8]>)] {
+    static EMBEDDED: OnceLock<Vec<(String, Cow<'static, [u8]>)>> = OnceLock::new();
+    EMBEDDED.get_or_init(|| {
+        let themes = Theme::builtin().into_iter().filter_map(|theme| {
+            let bytes = serde_json::to_vec_pretty(&theme).ok()?;
+            Some((
+                format!("{}/{}", THEME_DIR, theme.file_name()),
+                Cow::Owned(bytes),
+            ))
+        });
+        std::iter::once((
+            ICON.to_string(),
+            Cow::Borrowed(include_bytes!("assets/icon.png").as_slice()),
+        ))
+        .chain(themes)
+        .collect()
+    })
+}
+
+//Directory of user supplied assets, each replacing the embedded asset at the same path or adding a new one.
+pub fn override_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("lyfcal").join("assets"))
+}
+
+pub fn embedded(path: &str) -> Option<&'static [u8]> {
+    embedded_assets()
+        .iter()
+        .find(|(embedded_path, _)| embedded_path == path)
+        .map(|(_, bytes)| bytes.as_ref())
+}
+
+//Contents of an asset, the user's copy when there is one.
+pub fn load(path: &str) -> Result<Cow<'static, [u8]>, LyfcalError> {
+    load_from(override_dir().as_deref(), path)
+}
+
+fn load_from(override_dir: Option<&Path>, path: &str) -> Result<Cow<'static, [u8]>, LyfcalError> {
+    if let Some(file) = override_dir
+        .map(|dir| dir.join(path))
+        .filter(|file| file.is_file())
+    {
+        return std::fs::read(&file)
+            .map(Cow::Owned)
+            .map_err(|error| LyfcalError::Io {
+                action: "read",
+                path: file,
+                message: error.to_string(),
+            });
+    }
+    embedded(path)
+        .map(Cow::Borrowed)
+        .ok_or_else(|| LyfcalError::Io {
+            action: "read",
+            path: path.into(),
+            message: "no such asset".to_string(),
+        })
+}
+
+//Paths of the embedded and user supplied assets directly within a directory, i.e. "fonts".
+pub fn list(dir: &str) -> Vec<String> {
+    list_from(override_dir().as_deref(), dir)
+}
+
+fn list_from(override_dir: Option<&Path>, dir: &str) -> Vec<String> {
+    let prefix = format!("{}/", dir);
+    let mut paths: BTreeSet<String> = embedded_assets()
+        .iter()
+        .filter(|(path, _)| {
+            path.strip_prefix(&prefix)
+                .is_some_and(|name| !name.contains('/'))
+        })
+        .map(|(path, _)| path.to_string())
+        .collect();
+    let user_files = override_dir
+        .and_then(|override_dir| std::fs::read_dir(override_dir.join(dir)).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()));
+    for entry in user_files {
+        if let Some(name) = entry.file_name().to_str() {
+            paths.insert(format!("{}{}", prefix, name));
+        }
+    }
+    paths.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_themes_are_served_as_theme_files() {
+        let paths = list_from(None, THEME_DIR);
+        for theme in Theme::builtin() {
+            let path = format!("{}/{}", THEME_DIR, theme.file_name());
+            assert!(paths.contains(&path), "{}", path);
+            let bytes = embedded(&path).unwrap();
+            assert_eq!(Theme::parse(bytes, Path::new(&path)), Ok(theme));
+        }
+        assert!(embedded("themes/missing.json").is_none());
+    }
+
+    #[test]
+    fn user_assets_replace_and_add_to_the_embedded_ones() {
+        let dir = std::env::temp_dir().join(format!("lyfcal-assets-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join(THEME_DIR).join("nested")).unwrap();
+        std::fs::write(dir.join(ICON), b"icon").unwrap();
+        std::fs::write(dir.join(THEME_DIR).join("own.json"), b"{}").unwrap();
+
+        assert_eq!(load_from(Some(&dir), ICON).unwrap().as_ref(), b"icon");
+        let builtin = format!("{}/{}", THEME_DIR, Theme::builtin()[0].file_name());
+        assert_eq!(
+            load_from(Some(&dir), &builtin),
+            Ok(Cow::Borrowed(embedded(&builtin).unwrap()))
+        );
+        assert!(load_from(Some(&dir), "themes/missing.json").is_err());
+
+        let paths = list_from(Some(&dir), THEME_DIR);
+        assert!(paths.contains(&"themes/own.json".to_string()));
+        assert!(paths.contains(&builtin));
+        assert!(!paths.iter().any(|path| path.contains("nested")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,227 +1,249 @@
-use chrono::{Datelike, NaiveDate, Weekday};
+use chrono::NaiveDate;
 use eframe::egui::*;
-use std::collections::BTreeMap;
+use lyfcal_core::calendar::{Calendar, ConfigChange};
+use lyfcal_core::color::Color;
+use lyfcal_core::config::Config;
+use lyfcal_core::error::LyfcalError;
+use lyfcal_core::geometry as core;
+use lyfcal_core::layout::{GridLayout, LayoutMetrics};
 
 #[derive(Default, Debug)]
-struct Matrix {
-    //Each column contains 7 units for each day of the week.
-    col: usize,
-    row: usize,
-    unit_size: f32,
+pub struct DrawData {
+    pub calendar: Calendar,
+    grid_cache: GridCache,
 }
 
+//Grid geometry and tessellated mesh reused between frames. The layout is kept until the
+//viewport or layout settings change, the mesh until anything drawn changes.
 #[derive(Default, Debug)]
-pub struct DrawData {
-    pub config: super::config::Config,
-    pub events: BTreeMap<NaiveDate, String>,
+struct GridCache {
+    layout: Option<(LayoutKey, GridLayout)>,
+    mesh: Option<(MeshKey, Mesh)>,
 }
 
-impl DrawData {
-    pub fn initialize(&mut self, config: &super::config::Config) {
-        self.config = config.clone();
-    }
+#[derive(Debug, PartialEq)]
+struct LayoutKey {
+    area: Rect,
+    available: Vec2,
+}
 
-    //Function to populate events with every day from the birthdate up to the end of the life expectancy
-    pub fn populate_events(&mut self) {
-        self.events.clear();
-
-        let birth_year = self.config.birthdate.unwrap().year();
-        let end_year = birth_year + self.config.life_expectancy;
-        let duration = NaiveDate::from_ymd_opt(
-            end_year,
-            self.config.birthdate.unwrap().month(),
-            self.config.birthdate.unwrap().day(),
-        )
-        .unwrap()
-        .signed_duration_since(self.config.birthdate.unwrap())
-        .num_days() as i32;
-        let mut date_counter = self.config.birthdate.unwrap();
-        //Extra day to account of birthday
-        for _ in 0..(duration + 1) {
-            self.events
-                .insert(date_counter, format!("{}", date_counter.weekday()));
-            date_counter += chrono::Duration::days(1)
-        }
-    }
+#[derive(Debug, PartialEq)]
+struct MeshKey {
+    pixels_per_point: f32,
+    elapsed_date: NaiveDate,
+}
 
-    //Calculate to maximize unit size/spacing for the given screen space and spacing
-    fn calculate_matrix(&self, ui: &mut Ui) -> Matrix {
-        let birthday_offset = self
-            .config
-            .birthdate
-            .unwrap()
-            .weekday()
-            .num_days_from_monday() as usize;
-        let mut col_output: usize = 0;
-        let mut row_output: usize = 0;
-        let mut size_output: f32 = 0.0;
-
-        //Test for the number of columns needed to fit the total number of event entries.
-        for col_num in 1.. {
-            //Calculate the unit size for each number of columns.
-            let unit_size = ui.available_width()
-                / (col_num as f32 * 7.0
-                    + self.config.col_spacing * (col_num as f32 - 1.0)
-                    + 2.0 * self.config.border_spacing);
-            //Work out the maximum number of rows given the unit size.
-            let row_num = ((ui.available_height() - 2.0 * self.config.border_spacing * unit_size
-                + self.config.row_spacing * unit_size)
-                / (unit_size + self.config.row_spacing * unit_size))
-                as usize;
-            //Then check if the maximum number of event entries is greater than the total number of event entries.
-            if (col_num * 7) * row_num >= (self.events.len() - birthday_offset) {
-                col_output = col_num;
-                row_output = row_num;
-                size_output = unit_size;
-                break;
+impl DrawData {
+    //Take on a new config, dropping only the cached drawing state the change invalidates.
+    pub fn update_config(&mut self, config: &Config) -> Result<ConfigChange, LyfcalError> {
+        let change = self.calendar.update_config(config)?;
+        match change {
+            ConfigChange::Rebuild | ConfigChange::Relayout => {
+                self.grid_cache = GridCache::default()
             }
+            ConfigChange::Repaint => self.grid_cache.mesh = None,
+            ConfigChange::None => {}
         }
-        Matrix {
-            col: col_output,
-            row: row_output,
-            unit_size: size_output,
-        }
+        Ok(change)
     }
 
-    //Offset unit body to account for empty column
-    fn col_offset(&self, matrix: &Matrix) -> f32 {
-        let max_unit_num = matrix.col * matrix.row * 7;
-        let unit_num = self.events.len()
-            + self
-                .config
-                .birthdate
-                .unwrap()
-                .weekday()
-                .num_days_from_monday() as usize;
-        let col_capacity = matrix.row * 7;
-        let mut offset = 0.0;
-
-        //
-        //Line below occasionally causes crashes during window resizing due to overflow issue if left at usize
-
-        if (max_unit_num as i32 - unit_num as i32) >= col_capacity as i32 {
-            offset = matrix.unit_size * (7.0 + self.config.col_spacing) / (matrix.col as f32 - 2.0);
-        };
-        offset
+    pub fn populate_events(&mut self) -> Result<(), LyfcalError> {
+        self.grid_cache = GridCache::default();
+        self.calendar.populate_events()
     }
 
-    //Calculate unit location
-    fn calculate_pos(
-        &self,
-        col: usize,
-        row: usize,
-        unit_size: f32,
-        date: NaiveDate,
-        col_offset: f32,
-        offset: (f32, f32),
-    ) -> Rect {
-        let x = unit_size
-            * (self.config.border_spacing
-                + date.weekday().number_from_monday() as f32
-                + 7.0 * col as f32
-                + self.config.col_spacing * (col as f32)
-                - 1.0)
-            + col as f32 * col_offset
-            + offset.0;
-        let y = unit_size
-            * (self.config.border_spacing + row as f32 + self.config.row_spacing * (row as f32))
-            + offset.1;
-        Rect::from_min_size(
-            egui::pos2(
-                x + ((1.0 - self.config.unit_ratio) / 2.0) * unit_size,
-                y + ((1.0 - self.config.unit_ratio) / 2.0) * unit_size,
-            ),
-            egui::vec2(
-                self.config.unit_ratio * unit_size,
-                self.config.unit_ratio * unit_size,
-            ),
-        )
+    //How well the last laid out grid fits its area, none before the grid is first drawn.
+    pub fn layout_metrics(&self) -> Option<LayoutMetrics> {
+        self.grid_cache
+            .layout
+            .as_ref()
+            .map(|(_, layout)| layout.metrics)
     }
 
     //Draw logic
-    fn draw_unit(&self, ui: &mut Ui, rect: Rect, date: NaiveDate, unit_size: f32) {
-        // Determine the date type based on whether it's a weekday or weekend.
-        let date_type = if is_weekday(date) {
-            DateType::Weekday
-        } else {
-            DateType::Weekend
-        };
-
-        let rounding = unit_size * self.config.unit_ratio / 16.0;
+    fn draw_unit(&self, shapes: &mut Vec<Shape>, rect: Rect, date: NaiveDate, unit_size: f32) {
+        let config = &self.calendar.config;
+        let rounding = unit_size * config.unit_ratio / 16.0;
+        for color in self.calendar.unit_fills(date) {
+            shapes.push(Shape::rect_filled(rect, rounding, color32(color)));
+        }
 
-        // Determine if the date is elapsed or in the future.
-        let is_elapsed = date <= self.config.elapsed_date;
+        if config.notes.contains_key(&date) {
+            shapes.push(Shape::circle_filled(
+                rect.center(),
+                unit_size * config.unit_ratio * 0.2,
+                color32(config.color_today),
+            ));
+        }
 
-        // Use a match statement to determine the color based on the tuple of (is_elapsed, date_type).
-        let color = match (is_elapsed, date_type) {
-            (false, DateType::Weekday) => self.config.color_weekday,
-            (true, DateType::Weekday) => self.config.color_weekday_elapsed,
-            (false, DateType::Weekend) => self.config.color_weekend,
-            (true, DateType::Weekend) => self.config.color_weekend_elapsed,
+        if date == self.calendar.elapsed_date() {
+            shapes.push(Shape::rect_stroke(
+                rect,
+                rounding,
+                Stroke::new(unit_size * 0.1 + 0.5, color32(config.color_today)),
+            ));
         };
+    }
 
-        // Draw the rectangle with the determined color.
-        ui.painter().rect_filled(rect, rounding, color);
+    //Shapes for every unit of a laid out grid.
+    fn grid_shapes(&self, layout: &GridLayout) -> Vec<Shape> {
+        let mut shapes = Vec::with_capacity(self.calendar.events.len());
+        for (date, rect) in self.calendar.events.keys().zip(&layout.units) {
+            self.draw_unit(&mut shapes, rect_from_core(*rect), *date, layout.unit_size);
+        }
+        shapes
+    }
 
-        if is_birthday(date, self.config.birthdate.unwrap()) && !is_elapsed {
-            ui.painter()
-                .rect_filled(rect, rounding, self.config.color_birthday);
-        } else if is_birthday(date, self.config.birthdate.unwrap()) && is_elapsed {
-            ui.painter()
-                .rect_filled(rect, rounding, self.config.color_birthday_elapsed);
+    pub fn draw_lyfcal(&mut self, ui: &mut Ui) {
+        //Only lay out and tessellate the grid again when one of their inputs has changed.
+        let layout_key = LayoutKey {
+            area: ui.max_rect(),
+            available: ui.available_size(),
         };
-
-        if date == self.config.elapsed_date {
-            ui.painter().rect_stroke(
-                rect,
-                rounding,
-                Stroke::new(unit_size * 0.1 + 0.5, self.config.color_today),
+        if self.grid_cache.layout.as_ref().map(|(key, _)| key) != Some(&layout_key) {
+            let layout = self.calendar.grid_layout(
+                rect_to_core(layout_key.area),
+                core::vec2(layout_key.available.x, layout_key.available.y),
             );
+            self.grid_cache.layout = Some((layout_key, layout));
+            self.grid_cache.mesh = None;
+        }
+
+        let mesh_key = MeshKey {
+            pixels_per_point: ui.ctx().pixels_per_point(),
+            elapsed_date: self.calendar.elapsed_date(),
         };
-    }
+        if self.grid_cache.mesh.as_ref().map(|(key, _)| key) != Some(&mesh_key) {
+            let shapes = match &self.grid_cache.layout {
+                Some((_, layout)) => self.grid_shapes(layout),
+                None => Vec::new(),
+            };
+            self.grid_cache.mesh = Some((mesh_key, tessellate_shapes(ui.ctx(), shapes)));
+        }
 
-    pub fn draw_lyfcal(&mut self, ui: &mut Ui) {
-        //To allow exception to the first week shown where the week doesn't begin on a monday, extra day added for the birthday itself.
-        let matrix = self.calculate_matrix(ui);
-        let mut date_counter = self.config.birthdate.expect("No birthdate given.");
-
-        for col in 0..(matrix.col) {
-            for row in 0..(matrix.row) {
-                for _ in date_counter.weekday().num_days_from_monday() as usize..7 {
-                    if date_counter > *self.events.last_key_value().unwrap().0 {
-                        break;
-                    }
-                    self.draw_unit(
-                        ui,
-                        self.calculate_pos(
-                            col,
-                            row,
-                            matrix.unit_size,
-                            date_counter,
-                            self.col_offset(&matrix),
-                            (0.0, 0.0),
-                        ),
-                        date_counter,
-                        matrix.unit_size,
-                    );
-                    date_counter += chrono::Duration::days(1);
-                }
-            }
+        if let Some((_, mesh)) = &self.grid_cache.mesh {
+            ui.painter().add(Shape::mesh(mesh.clone()));
         }
     }
 }
 
 //================================================== MISC. FUNCTIONS ==================================================
 
-fn is_birthday(date: NaiveDate, birthdate: NaiveDate) -> bool {
-    date.month() == birthdate.month() && date.day() == birthdate.day()
+pub fn color32(color: Color) -> Color32 {
+    let [r, g, b, a] = color.to_array();
+    Color32::from_rgba_premultiplied(r, g, b, a)
+}
+
+pub fn color_from_egui(color: Color32) -> Color {
+    let [r, g, b, a] = color.to_array();
+    Color::from_rgba_premultiplied(r, g, b, a)
+}
+
+fn rect_to_core(rect: Rect) -> core::Rect {
+    core::Rect {
+        min: core::pos2(rect.min.x, rect.min.y),
+        max: core::pos2(rect.max.x, rect.max.y),
+    }
 }
 
-fn is_weekday(date: NaiveDate) -> bool {
-    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+fn rect_from_core(rect: core::Rect) -> Rect {
+    Rect::from_min_max(pos2(rect.min.x, rect.min.y), pos2(rect.max.x, rect.max.y))
 }
 
-enum DateType {
-    Weekday,
-    Weekend,
+//Tessellate shapes into a single mesh with the context's current tessellation settings.
+fn tessellate_shapes(ctx: &Context, shapes: Vec<Shape>) -> Mesh {
+    let mut tessellator = epaint::Tessellator::new(
+        ctx.pixels_per_point(),
+        ctx.tessellation_options(|options| *options),
+        ctx.fonts(|fonts| fonts.font_image_size()),
+        Vec::new(),
+    );
+    let mut mesh = Mesh::default();
+    for shape in shapes {
+        match shape {
+            //Rounding under a pixel is invisible, so plain quads keep the mesh a fraction of the size.
+            Shape::Rect(rect_shape)
+                if rect_shape.stroke.is_empty()
+                    && rect_shape.rounding.nw * ctx.pixels_per_point() < 1.0 =>
+            {
+                mesh.add_colored_rect(rect_shape.rect, rect_shape.fill)
+            }
+            shape => tessellator.tessellate_shape(shape, &mut mesh),
+        }
+    }
+    mesh
+}
+
+//================================================== BENCHMARKS ==================================================
+
+//Frame time of the life viewport for a 120 year lifespan, run with:
+//cargo test --release bench_draw_lyfcal -- --ignored --nocapture
+#[cfg(test)]
+mod benches {
+    use super::*;
+    use lyfcal_core::testing;
+    use std::time::{Duration, Instant};
+
+    const FRAMES: u32 = 60;
+
+    fn draw_data_120_years() -> DrawData {
+        let mut config = lyfcal_core::config::Config {
+            birthdate: NaiveDate::from_ymd_opt(1950, 1, 1),
+            life_expectancy: 120,
+            holiday_region: Some(lyfcal_core::holiday::HolidayRegion::EnglandWales),
+            ..testing::config()
+        };
+        config.highlights.push(lyfcal_core::config::Highlight {
+            name: "payday".to_string(),
+            start: NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
+            rule: "FREQ=MONTHLY;BYMONTHDAY=-1".parse().unwrap(),
+            color: lyfcal_core::color::Color::from_rgb(255, 215, 0),
+            color_elapsed: lyfcal_core::color::Color::from_rgb(96, 96, 96),
+        });
+        let mut draw_data = DrawData::default();
+        draw_data.update_config(&config).unwrap();
+        draw_data
+    }
+
+    //Average time of a full egui frame, including egui's own tessellation of the output.
+    fn time_frames(draw_data: &mut DrawData, invalidate: bool) -> Duration {
+        let ctx = Context::default();
+        let raw_input = || RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(1920.0, 1080.0))),
+            ..Default::default()
+        };
+        let frame = |draw_data: &mut DrawData| {
+            let output = ctx.run(raw_input(), |ctx| {
+                CentralPanel::default()
+                    .frame(Frame::none())
+                    .show(ctx, |ui| draw_data.draw_lyfcal(ui));
+            });
+            ctx.tessellate(output.shapes, output.pixels_per_point)
+        };
+        //Warm up fonts and the cache.
+        frame(draw_data);
+
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            if invalidate {
+                draw_data.grid_cache = GridCache::default();
+            }
+            assert!(!frame(draw_data).is_empty());
+        }
+        start.elapsed() / FRAMES
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_draw_lyfcal() {
+        let mut draw_data = draw_data_120_years();
+        let rebuilt = time_frames(&mut draw_data, true);
+        let cached = time_frames(&mut draw_data, false);
+        println!(
+            "120 years ({} days): rebuilt every frame {:?}, cached mesh {:?} per frame",
+            draw_data.calendar.events.len(),
+            rebuilt,
+            cached
+        );
+    }
 }
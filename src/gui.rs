@@ -1,51 +1,453 @@
-use chrono::{Datelike, Local, NaiveDate};
+use chrono::{Days, NaiveDate, Weekday};
 use core::f32;
 use eframe::egui::*;
+use std::collections::{BTreeSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread::JoinHandle;
+use std::time::Instant;
 
-#[derive(Default, Debug)]
+use super::assets;
+use super::draw::{color32, color_from_egui, DrawData};
+use super::wallpaper::Screen;
+use lyfcal_core::calendar::ConfigChange;
+use lyfcal_core::clock::{Clock, DateClock, SystemClock};
+use lyfcal_core::color::Color;
+use lyfcal_core::config::{
+    FlowMode, GridAlignment, GridSizing, Highlight, HorizontalFlow, LeapDayPolicy, Monitor,
+    VerticalFlow, WeekOrientation, WindowGeometry, WindowLayer, BORDER_SPACING_RANGE,
+    COLUMN_COUNT_RANGE, SPACING_RANGE, UNIT_RATIO_RANGE, UNIT_SIZE_RANGE,
+};
+use lyfcal_core::error::{LyfcalError, MAX_AGE};
+use lyfcal_core::holiday::HolidayRegion;
+use lyfcal_core::recurrence::RecurrenceRule;
+use lyfcal_core::schedule::{format_pattern, parse_pattern, ShiftPreset, WorkSchedule};
+use lyfcal_core::theme::{Palette, Theme, WindowVisuals};
+use lyfcal_core::vision::{contrast_warnings, ColorVision};
+
+//Highlight being entered in the config window before it is added to the config.
+#[derive(Debug)]
+struct HighlightDraft {
+    name: String,
+    rule: String,
+    start: NaiveDate,
+    error: Option<String>,
+}
+
+impl HighlightDraft {
+    fn new(today: NaiveDate) -> Self {
+        Self {
+            name: String::new(),
+            rule: "FREQ=MONTHLY;BYMONTHDAY=1".to_string(),
+            start: today,
+            error: None,
+        }
+    }
+}
+
+//Work schedule being entered in the config window before it is added to the config.
+#[derive(Debug)]
+struct ScheduleDraft {
+    name: String,
+    pattern: String,
+    anchor: NaiveDate,
+    has_end: bool,
+    until: NaiveDate,
+    error: Option<String>,
+}
+
+impl ScheduleDraft {
+    fn new(today: NaiveDate) -> Self {
+        Self {
+            name: String::new(),
+            pattern: ShiftPreset::FourOnFourOff.pattern().to_string(),
+            anchor: today,
+            has_end: false,
+            until: today,
+            error: None,
+        }
+    }
+}
+
+//Preview of the grid at another elapsed date, scrubbed or animated across the lifespan.
+#[derive(Debug, Default)]
+struct TimeTravel {
+    enabled: bool,
+    playing: bool,
+    //Days since the first day of the lifespan.
+    position: f64,
+}
+
+//Themes offered in the config window, each drawn as a sample grid in its own colours.
+#[derive(Debug, Default)]
+struct ThemeGallery {
+    //Built in themes followed by those in the user's theme directory, none until first shown.
+    themes: Option<Vec<(Theme, DrawData)>>,
+    export_name: String,
+    import_path: String,
+    //Outcome of the last import or export, or the user themes that couldn't be read.
+    status: Option<Result<String, Vec<LyfcalError>>>,
+}
+
+//Sample grid in the edited colours as perceived with a chosen colour vision.
+#[derive(Debug)]
+struct VisionPreview {
+    vision: ColorVision,
+    draw_data: DrawData,
+}
+
+impl Default for VisionPreview {
+    fn default() -> Self {
+        Self {
+            vision: ColorVision::Deuteranopia,
+            draw_data: Default::default(),
+        }
+    }
+}
+
+const THEME_SAMPLE_SIZE: Vec2 = vec2(120.0, 64.0);
+
+//Seconds an animated time travel takes to cross the whole lifespan.
+const TIME_TRAVEL_SECONDS: f64 = 12.0;
+
+const SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+//How often the frame counts are logged, in release builds too, to keep an eye on idle repaints.
+const FRAME_REPORT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+#[derive(Debug)]
 pub struct LyfcalApp {
-    config: super::config::Config,
-    draw_data: super::draw::DrawData,
-    show_immediate_viewport: bool,
-    //show_deferred_viewport: Arc<AtomicBool>,
+    config: lyfcal_core::config::Config,
+    life_viewport: Arc<LifeViewport>,
+    highlight_draft: HighlightDraft,
+    schedule_draft: ScheduleDraft,
+    time_travel: TimeTravel,
+    theme_gallery: ThemeGallery,
+    vision_preview: VisionPreview,
+    last_config_change: ConfigChange,
+    //Placement the life viewport was opened with, maximized if none was remembered for the monitor.
+    opened_geometry: Option<WindowGeometry>,
+    //Monitor of the config window, which the wallpaper is rendered for.
+    screen: Option<Screen>,
+    wallpaper_job: Option<JoinHandle<Result<PathBuf, LyfcalError>>>,
+    //Outcome of the last wallpaper generated, the written path or an error.
+    wallpaper_status: Option<Result<PathBuf, LyfcalError>>,
+    //Config as last written to disk, none if the saved config couldn't be read and must not be overwritten.
+    saved_config: Option<lyfcal_core::config::Config>,
+    last_save: Instant,
+    persist_error: Option<LyfcalError>,
+    //Reasons the edited config can't be applied, shown next to the settings at fault.
+    config_problems: Vec<LyfcalError>,
+    clock: DateClock,
+    config_frames: FrameCounter,
+    last_frame_report: Instant,
+    //Commands forwarded by later invocations, none if another instance owns the socket.
+    #[cfg(unix)]
+    ipc_server: Option<super::ipc::Server>,
+    //Set by an explicit quit, the only close that isn't turned into hiding the config window.
+    #[cfg(unix)]
+    quitting: bool,
+}
+
+//State shared with the deferred life viewport, which repaints independently of the config window.
+#[derive(Debug, Default)]
+struct LifeViewport {
+    draw_data: RwLock<super::draw::DrawData>,
+    frames: Mutex<FrameCounter>,
+    open: AtomicBool,
+    //Passthrough is suspended and decorations shown so the widget can be moved and resized.
+    edit_mode: AtomicBool,
+    //Latest placement reported by the viewport, waiting to be stored in the config.
+    geometry: Mutex<Option<(Monitor, WindowGeometry)>>,
+    //Last placement reported, so an unmoved window doesn't wake the config window every frame.
+    reported_geometry: Mutex<Option<(Monitor, WindowGeometry)>>,
+}
+
+impl LifeViewport {
+    //A panic while drawing leaves the data usable, so poisoning is ignored.
+    fn draw_data(&self) -> RwLockReadGuard<'_, super::draw::DrawData> {
+        self.draw_data
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn draw_data_mut(&self) -> RwLockWriteGuard<'_, super::draw::DrawData> {
+        self.draw_data
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn frames(&self) -> MutexGuard<'_, FrameCounter> {
+        self.frames.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn geometry(&self) -> MutexGuard<'_, Option<(Monitor, WindowGeometry)>> {
+        self.geometry.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    //Hand a changed placement over to the config window, returning whether there was one.
+    fn report_geometry(&self, placement: (Monitor, WindowGeometry)) -> bool {
+        let mut reported = self
+            .reported_geometry
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if *reported == Some(placement) {
+            return false;
+        }
+        *reported = Some(placement);
+        *self.geometry() = Some(placement);
+        true
+    }
+
+    fn toggle_edit_mode(&self) {
+        self.edit_mode.fetch_xor(true, Ordering::Relaxed);
+    }
+
+    fn id() -> egui::ViewportId {
+        egui::ViewportId::from_hash_of("life_viewport")
+    }
+}
+
+impl Default for LyfcalApp {
+    fn default() -> Self {
+        Self::new(Box::new(SystemClock))
+    }
 }
 
 impl LyfcalApp {
-    //When initialized, data from the config is passed to draw_data.
-    fn initialize(&mut self) {
-        self.draw_data.initialize(&self.config);
+    //App with every date derived from the given clock.
+    pub fn new(clock: Box<dyn Clock>) -> Self {
+        let clock = DateClock::new(clock);
+        let today = clock.today();
+        let (config, persist_error) = match lyfcal_core::config::Config::load(clock.source()) {
+            Ok(config) => (config, None),
+            Err(error) => (
+                lyfcal_core::config::Config::new(clock.source()),
+                Some(error),
+            ),
+        };
+        Self {
+            saved_config: persist_error.is_none().then(|| config.clone()),
+            last_save: Instant::now(),
+            last_frame_report: Instant::now(),
+            persist_error,
+            config_problems: config.problems(),
+            config,
+            life_viewport: Default::default(),
+            highlight_draft: HighlightDraft::new(today),
+            schedule_draft: ScheduleDraft::new(today),
+            time_travel: Default::default(),
+            theme_gallery: Default::default(),
+            vision_preview: Default::default(),
+            last_config_change: ConfigChange::None,
+            opened_geometry: None,
+            screen: None,
+            wallpaper_job: None,
+            wallpaper_status: None,
+            clock,
+            config_frames: Default::default(),
+            #[cfg(unix)]
+            ipc_server: None,
+            #[cfg(unix)]
+            quitting: false,
+        }
+    }
+
+    //Write config edits to disk, at most once every SAVE_INTERVAL while they keep coming.
+    //Configs with problems aren't written, so the command line modes keep working mid edit.
+    fn save_config(&mut self, ctx: &egui::Context) {
+        if self
+            .saved_config
+            .as_ref()
+            .is_none_or(|saved| *saved == self.config)
+            || !self.config_problems.is_empty()
+        {
+            return;
+        }
+        let wait = SAVE_INTERVAL.saturating_sub(self.last_save.elapsed());
+        if !wait.is_zero() {
+            ctx.request_repaint_after(wait);
+            return;
+        }
+        self.last_save = Instant::now();
+        match self.config.save() {
+            Ok(()) => {
+                self.saved_config = Some(self.config.clone());
+                self.persist_error = None;
+            }
+            Err(error) => self.persist_error = Some(error),
+        }
+    }
+
+    //Log the frames rendered over the last hour once every FRAME_REPORT_INTERVAL, returns the time to the next report.
+    fn report_frames(&mut self) -> std::time::Duration {
+        let wait = FRAME_REPORT_INTERVAL.saturating_sub(self.last_frame_report.elapsed());
+        if !wait.is_zero() {
+            return wait;
+        }
+        self.last_frame_report = Instant::now();
+        eprintln!(
+            "lyfcal: {} config window and {} life grid frames in the last hour",
+            self.config_frames.frames_last_hour(),
+            self.life_viewport.frames().frames_last_hour()
+        );
+        FRAME_REPORT_INTERVAL
+    }
+
+    //Apply the config and open the life viewport where it was last placed on this monitor.
+    fn show_life_viewport(&mut self, ctx: &egui::Context) {
+        self.apply_config();
+        if !self.life_viewport.open.load(Ordering::Relaxed) {
+            //The monitor the config window is on, where the life viewport opens.
+            self.opened_geometry = ctx
+                .input(|i| {
+                    Some(monitor(
+                        i.viewport().outer_rect?,
+                        i.viewport().monitor_size?,
+                    ))
+                })
+                .and_then(|monitor| self.config.window_geometry.get(&monitor))
+                .copied();
+        }
+        self.life_viewport.open.store(true, Ordering::Relaxed);
+        ctx.request_repaint_of(LifeViewport::id());
+    }
+
+    #[cfg(unix)]
+    pub fn serve(&mut self, server: super::ipc::Server) {
+        self.ipc_server = Some(server);
+    }
+
+    //Carry out a command given on the command line, directly or forwarded from another invocation.
+    #[cfg(unix)]
+    pub fn handle_command(
+        &mut self,
+        ctx: &egui::Context,
+        command: super::ipc::Command,
+    ) -> Result<(), LyfcalError> {
+        use super::ipc::Command;
+        match command {
+            Command::OpenConfig => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+            Command::ShowViewport => self.show_life_viewport(ctx),
+            Command::AddNote { date, text } => {
+                self.config.notes.insert(date, text);
+                //Only the note reaches the grid, other edits still wait for apply.
+                let mut draw_data = self.life_viewport.draw_data_mut();
+                let config = lyfcal_core::config::Config {
+                    notes: self.config.notes.clone(),
+                    ..draw_data.calendar.config.clone()
+                };
+                self.last_config_change = draw_data.update_config(&config)?;
+                ctx.request_repaint_of(LifeViewport::id());
+            }
+            Command::Reload => {
+                self.config = lyfcal_core::config::Config::load(self.clock.source())?;
+                self.saved_config = Some(self.config.clone());
+                self.persist_error = None;
+                self.apply_config();
+                self.life_viewport.draw_data_mut().populate_events()?;
+                self.last_config_change = ConfigChange::Rebuild;
+                ctx.request_repaint_of(LifeViewport::id());
+            }
+            Command::Quit => self.quit(ctx),
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn quit(&mut self, ctx: &egui::Context) {
+        self.quitting = true;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    }
+
+    //While commands can reach the instance, closing the config window only hides it and the instance
+    //keeps running, for later invocations to open it again, until it is quit.
+    #[cfg(unix)]
+    fn hide_on_close(&self, ctx: &egui::Context) {
+        if self.ipc_server.is_some()
+            && !self.quitting
+            && ctx.input(|i| i.viewport().close_requested())
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        }
+    }
+
+    #[cfg(unix)]
+    fn handle_ipc_requests(&mut self, ctx: &egui::Context) {
+        while let Some(request) = self
+            .ipc_server
+            .as_ref()
+            .and_then(|server| server.try_recv())
+        {
+            let result = self.handle_command(ctx, request.command.clone());
+            request.reply(result);
+        }
+    }
+
+    //When applied, data from the config is passed to draw_data, which recomputes only what changed.
+    //A config with problems is held back, leaving the grid as last applied.
+    fn apply_config(&mut self) -> ConfigChange {
+        let Ok(change) = self
+            .life_viewport
+            .draw_data_mut()
+            .update_config(&self.config)
+        else {
+            return ConfigChange::None;
+        };
+        if change != ConfigChange::None {
+            self.last_config_change = change;
+        }
+        change
     }
 
     #[cfg(debug_assertions)]
     fn debug_println(&mut self, ui: &mut Ui) {
-        if cfg!(debug_assertions) {
-            let birth_year = self.config.birthdate.unwrap().year();
-            let end_year = birth_year + self.config.life_expectancy;
-            let duration = NaiveDate::from_ymd_opt(
-                end_year,
-                self.config.birthdate.unwrap().month(),
-                self.config.birthdate.unwrap().day(),
-            )
-            .unwrap()
-            .signed_duration_since(
-                NaiveDate::from_ymd_opt(
-                    birth_year,
-                    self.config.birthdate.unwrap().month(),
-                    self.config.birthdate.unwrap().day(),
-                )
-                .unwrap(),
-            )
-            .num_days() as i32;
+        if let Ok((birthdate, end)) = self.config.lifespan() {
+            ui.label(format!(
+                "life expectancy: {} days",
+                (end - birthdate).num_days()
+            ));
+        }
 
-            ui.label(format!("life expectancy: {} days", duration));
+        let draw_data = self.life_viewport.draw_data();
+        ui.label(format!(
+            "elapsed date: {}",
+            draw_data.calendar.elapsed_date()
+        ));
 
-            ui.label(format!("elapsed date: {}", self.config.elapsed_date));
+        ui.label(format!("event number: {}", draw_data.calendar.events.len()));
 
-            ui.label(format!("event number: {}", self.draw_data.events.len()));
-        }
+        let (weekdays, weekends) = draw_data.calendar.remaining_day_counts();
+        ui.label(format!("weekdays remaining: {}", weekdays));
+
+        ui.label(format!("weekend days remaining: {}", weekends));
+
+        ui.label(format!(
+            "working days until retirement: {}",
+            draw_data.calendar.working_days_remaining()
+        ));
+
+        ui.label(format!("last config change: {:?}", self.last_config_change));
+
+        ui.label(format!(
+            "config frames in the last hour: {}",
+            self.config_frames.frames_last_hour()
+        ));
+
+        ui.label(format!(
+            "viewport frames in the last hour: {}",
+            self.life_viewport.frames().frames_last_hour()
+        ));
     }
 
     fn draw_config_ui(&mut self, ui: &mut egui::Ui) {
+        self.config_problems = self.config.problems();
         let mut style = (*ui.ctx().style()).clone();
         style.spacing.item_spacing.x = 4.0;
         ui.ctx().set_style(style);
@@ -60,9 +462,34 @@ impl LyfcalApp {
                 ui.end_row();
                 self.ui_birthdate_picker(ui);
                 ui.end_row();
+                self.ui_leap_day_combobox(ui);
+                ui.end_row();
                 self.ui_life_expectancy_input(ui);
                 ui.end_row();
                 self.ui_elapsed_date_picker(ui);
+                ui.end_row();
+                self.ui_time_travel_slider(ui);
+                ui.end_row();
+                self.ui_week_start_combobox(ui);
+                ui.end_row();
+                self.ui_weekend_days_toggles(ui);
+                ui.end_row();
+                self.ui_holiday_region_combobox(ui);
+            });
+        ui.add_space(8.0);
+        self.ui_theme_gallery(ui);
+        ui.add_space(8.0);
+        egui::Grid::new("themeconfiggrid")
+            .min_col_width(grid_col_width(ui, 2))
+            .max_col_width(grid_col_width(ui, 2))
+            .striped(true)
+            .spacing([0.0, 8.0])
+            .show(ui, |ui| {
+                self.ui_theme_export_input(ui);
+                ui.end_row();
+                self.ui_theme_import_input(ui);
+                ui.end_row();
+                self.ui_theme_status_label(ui);
             });
         ui.add_space(8.0);
         egui::Grid::new("displayconfiggrid")
@@ -77,10 +504,18 @@ impl LyfcalApp {
                 ui.end_row();
                 self.ui_weekend_colorpicker(ui);
                 ui.end_row();
+                self.ui_holiday_colorpicker(ui);
+                ui.end_row();
+                self.ui_on_shift_colorpicker(ui);
+                ui.end_row();
+                self.ui_off_shift_colorpicker(ui);
+                ui.end_row();
                 self.ui_birthday_colorpicker(ui);
                 ui.end_row();
                 self.ui_today_colorpicker(ui);
                 ui.end_row();
+                self.ui_vision_preview(ui);
+                ui.end_row();
                 self.ui_unit_ratio_slider(ui);
                 ui.end_row();
                 self.ui_column_spacing_slider(ui);
@@ -88,51 +523,170 @@ impl LyfcalApp {
                 self.ui_row_spacing_slider(ui);
                 ui.end_row();
                 self.ui_border_spacing_slider(ui);
+                ui.end_row();
+                self.ui_week_orientation_combobox(ui);
+                ui.end_row();
+                self.ui_flow_direction_comboboxes(ui);
+                ui.end_row();
+                self.ui_flow_mode_combobox(ui);
+                ui.end_row();
+                self.ui_grid_sizing_combobox(ui);
+                ui.end_row();
+                self.ui_grid_alignment_combobox(ui);
+                ui.end_row();
+                self.ui_grid_fit_label(ui);
+            });
+        ui.add_space(8.0);
+        egui::Grid::new("highlightsconfiggrid")
+            .min_col_width(grid_col_width(ui, 2))
+            .max_col_width(grid_col_width(ui, 2))
+            .striped(true)
+            .spacing([0.0, 8.0])
+            .show(ui, |ui| {
+                self.ui_highlights_config_heading(ui);
+                ui.end_row();
+                self.ui_highlight_list(ui);
+                self.ui_highlight_name_input(ui);
+                ui.end_row();
+                self.ui_highlight_rule_input(ui);
+                ui.end_row();
+                self.ui_highlight_start_picker(ui);
+                ui.end_row();
+                self.ui_highlight_add_button(ui);
+            });
+        ui.add_space(8.0);
+        egui::Grid::new("widgetconfiggrid")
+            .min_col_width(grid_col_width(ui, 2))
+            .max_col_width(grid_col_width(ui, 2))
+            .striped(true)
+            .spacing([0.0, 8.0])
+            .show(ui, |ui| {
+                self.ui_widget_config_heading(ui);
+                ui.end_row();
+                self.ui_widget_window_checkboxes(ui);
+                ui.end_row();
+                self.ui_window_layer_combobox(ui);
+                ui.end_row();
+                self.ui_widget_edit_mode_toggle(ui);
+            });
+        ui.add_space(8.0);
+        egui::Grid::new("wallpaperconfiggrid")
+            .min_col_width(grid_col_width(ui, 2))
+            .max_col_width(grid_col_width(ui, 2))
+            .striped(true)
+            .spacing([0.0, 8.0])
+            .show(ui, |ui| {
+                self.ui_wallpaper_config_heading(ui);
+                ui.end_row();
+                self.ui_wallpaper_enable_checkbox(ui);
+                ui.end_row();
+                self.ui_wallpaper_path_inputs(ui);
+                ui.end_row();
+                self.ui_wallpaper_command_input(ui);
+                ui.end_row();
+                self.ui_wallpaper_generate_button(ui);
+            });
+        ui.add_space(8.0);
+        egui::Grid::new("schedulesconfiggrid")
+            .min_col_width(grid_col_width(ui, 2))
+            .max_col_width(grid_col_width(ui, 2))
+            .striped(true)
+            .spacing([0.0, 8.0])
+            .show(ui, |ui| {
+                self.ui_schedules_config_heading(ui);
+                ui.end_row();
+                self.ui_retirement_age_input(ui);
+                ui.end_row();
+                self.ui_schedule_list(ui);
+                self.ui_schedule_name_input(ui);
+                ui.end_row();
+                self.ui_schedule_pattern_input(ui);
+                ui.end_row();
+                self.ui_schedule_range_pickers(ui);
+                ui.end_row();
+                self.ui_schedule_add_button(ui);
             });
         ui.add_space(12.0);
         ui.separator();
         ui.add_space(8.0);
-        self.draw_initialize_button(ui);
+        self.draw_apply_button(ui);
     }
 
-    fn draw_initialize_button(&mut self, ui: &mut egui::Ui) {
+    fn draw_apply_button(&mut self, ui: &mut egui::Ui) {
         ui.vertical_centered(|ui| {
-            if ui.button("initialize").clicked() {
-                self.initialize();
-                self.draw_data.populate_events();
-                self.show_immediate_viewport = true;
-                /* == DEFFERRED VIEWPORT ==
-                let current_value = self.show_deferred_viewport.load(Ordering::Relaxed);
-                self.show_deferred_viewport
-                    .store(!current_value, Ordering::Relaxed);
-                */
+            ui.checkbox(&mut self.config.live_preview, "live preview")
+                .on_hover_text("apply every change to the grid as it is made");
+            let label = if self.config.live_preview {
+                "show"
+            } else {
+                "apply"
+            };
+            if ui
+                .add_enabled(self.config_problems.is_empty(), egui::Button::new(label))
+                .on_disabled_hover_text("fix the settings marked above first")
+                .clicked()
+            {
+                self.show_life_viewport(ui.ctx());
+            }
+            if let Some(error) = &self.persist_error {
+                //Without a config read from disk there is none to save over.
+                let text = match self.saved_config {
+                    Some(_) => error.to_string(),
+                    None => format!("{}, changes won't be saved", error),
+                };
+                ui.colored_label(ui.visuals().error_fg_color, text);
             }
         });
     }
 
+    //Rows under a setting for each of its problems, in the grid's value column.
+    fn ui_config_problems(&self, ui: &mut egui::Ui, belongs: impl Fn(&LyfcalError) -> bool) {
+        for problem in self
+            .config_problems
+            .iter()
+            .filter(|problem| belongs(problem))
+        {
+            ui.end_row();
+            ui.label("");
+            ui.colored_label(ui.visuals().error_fg_color, problem.to_string());
+        }
+    }
+
     fn ui_birthdate_picker(&mut self, ui: &mut egui::Ui) {
         ui.label("birthdate:");
-        if let Some(ref mut birthdate) = self.config.birthdate {
-            let mut date = *birthdate;
-            if ui
-                .add_sized(
-                    [ui.available_width(), ui.spacing().interact_size.y],
-                    egui_extras::DatePickerButton::new(&mut date),
-                )
-                .changed()
-            {
-                *birthdate = date;
-            }
-        } else {
-            // Handle the case where birthdate is None, e.g., set an initial date or show a placeholder
-            let date = chrono::NaiveDate::from_ymd_opt(2000, 1, 1); // or some other default
-            if ui
-                .add(egui_extras::DatePickerButton::new(&mut date.unwrap()))
-                .changed()
-            {
-                self.config.birthdate = Some(date.unwrap());
-            }
+        //Without a birthdate the picker starts from a default date.
+        let mut date = self
+            .config
+            .birthdate
+            .unwrap_or(NaiveDate::from_ymd_opt(2000, 1, 1).unwrap_or_default());
+        if ui
+            .add_sized(
+                [ui.available_width(), ui.spacing().interact_size.y],
+                egui_extras::DatePickerButton::new(&mut date),
+            )
+            .changed()
+        {
+            self.config.birthdate = Some(date);
         }
+        self.ui_config_problems(ui, |problem| {
+            matches!(
+                problem,
+                LyfcalError::MissingBirthdate | LyfcalError::LifespanOutOfRange { .. }
+            )
+        });
+    }
+
+    fn ui_leap_day_combobox(&mut self, ui: &mut egui::Ui) {
+        ui.label("leap day birthday:")
+            .on_hover_text("day a 29 february birthday falls on in other years");
+        egui::ComboBox::from_id_source("leapdaycombobox")
+            .width(ui.available_width())
+            .selected_text(self.config.leap_day_policy.label())
+            .show_ui(ui, |ui| {
+                for policy in LeapDayPolicy::ALL {
+                    ui.selectable_value(&mut self.config.leap_day_policy, policy, policy.label());
+                }
+            });
     }
 
     fn ui_life_expectancy_input(&mut self, ui: &mut egui::Ui) {
@@ -144,7 +698,7 @@ impl LyfcalApp {
                 ui.add_sized(
                     [ui.available_width(), ui.spacing().interact_size.y],
                     egui::DragValue::new(&mut self.config.life_expectancy)
-                        .range(1..=120)
+                        .range(1..=MAX_AGE)
                         .suffix(" years"),
                 );
                 ui.end_row();
@@ -161,27 +715,34 @@ impl LyfcalApp {
                         .show(ui, |ui| {
                             ui.vertical_centered_justified(|ui| {
                                 if ui.button("−10").clicked() {
-                                    self.config.life_expectancy -= 10;
+                                    self.config.life_expectancy =
+                                        (self.config.life_expectancy - 10).max(1);
                                 }
                             });
                             ui.vertical_centered_justified(|ui| {
                                 if ui.button("−1").clicked() {
-                                    self.config.life_expectancy -= 1;
+                                    self.config.life_expectancy =
+                                        (self.config.life_expectancy - 1).max(1);
                                 }
                             });
                             ui.vertical_centered_justified(|ui| {
                                 if ui.button("+1").clicked() {
-                                    self.config.life_expectancy += 1;
+                                    self.config.life_expectancy =
+                                        (self.config.life_expectancy + 1).min(MAX_AGE);
                                 }
                             });
                             ui.vertical_centered_justified(|ui| {
                                 if ui.button("+10").clicked() {
-                                    self.config.life_expectancy += 10;
+                                    self.config.life_expectancy =
+                                        (self.config.life_expectancy + 10).min(MAX_AGE);
                                 }
                             });
                         });
                 });
             });
+        self.ui_config_problems(ui, |problem| {
+            matches!(problem, LyfcalError::LifeExpectancy(_))
+        });
     }
 
     fn ui_elapsed_date_picker(&mut self, ui: &mut egui::Ui) {
@@ -190,9 +751,9 @@ impl LyfcalApp {
             ui.checkbox(&mut self.config.elapsed_date_bool, "now ")
                 .on_hover_text("set current date date to date elapsed");
             ui.add_enabled_ui(!self.config.elapsed_date_bool, |ui| {
-                let mut date = Local::now().date_naive();
+                let mut date = self.clock.today();
                 if self.config.elapsed_date_bool {
-                    self.config.elapsed_date = Local::now().date_naive();
+                    self.config.elapsed_date = self.clock.today();
                     if ui
                         .add_sized(
                             [ui.available_width(), ui.spacing().interact_size.y],
@@ -213,8 +774,155 @@ impl LyfcalApp {
         });
     }
 
+    fn ui_time_travel_slider(&mut self, ui: &mut egui::Ui) {
+        ui.label("time travel:")
+            .on_hover_text("preview the grid at another elapsed date");
+        let (first_day, last_index) = {
+            let draw_data = self.life_viewport.draw_data();
+            (
+                draw_data
+                    .calendar
+                    .events
+                    .first_key_value()
+                    .map(|(date, _)| *date),
+                draw_data.calendar.events.len().saturating_sub(1) as f64,
+            )
+        };
+        ui.add_enabled_ui(first_day.is_some(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut self.time_travel.enabled, "").changed() {
+                    self.time_travel.playing = false;
+                }
+                ui.add_enabled_ui(self.time_travel.enabled, |ui| {
+                    let play_label = if self.time_travel.playing {
+                        "⏸"
+                    } else {
+                        "▶"
+                    };
+                    if ui.small_button(play_label).clicked() {
+                        if !self.time_travel.playing && self.time_travel.position >= last_index {
+                            self.time_travel.position = 0.0;
+                        }
+                        self.time_travel.playing = !self.time_travel.playing;
+                    }
+                    ui.spacing_mut().slider_width = ui.available_width();
+                    let slider = ui.add(
+                        egui::Slider::new(&mut self.time_travel.position, 0.0..=last_index)
+                            .step_by(1.0)
+                            .show_value(false),
+                    );
+                    if slider.dragged() {
+                        self.time_travel.playing = false;
+                    }
+                    if let Some(first_day) = first_day {
+                        let date =
+                            first_day + chrono::Duration::days(self.time_travel.position as i64);
+                        slider.on_hover_text(format!("{}", date));
+                    }
+                });
+            });
+        });
+    }
+
+    //Advance a playing time travel and hand the previewed date to draw_data, returning whether the shown date moved.
+    fn update_time_travel(&mut self, ctx: &egui::Context) -> bool {
+        let mut draw_data = self.life_viewport.draw_data_mut();
+        let last_index = draw_data.calendar.events.len().saturating_sub(1) as f64;
+        if self.time_travel.playing {
+            let dt = ctx.input(|i| i.stable_dt).min(0.1) as f64;
+            self.time_travel.position += last_index / TIME_TRAVEL_SECONDS * dt;
+            if self.time_travel.position >= last_index {
+                self.time_travel.playing = false;
+            }
+            ctx.request_repaint();
+        }
+        self.time_travel.position = self.time_travel.position.clamp(0.0, last_index);
+
+        let preview_date = match draw_data.calendar.events.first_key_value() {
+            Some((first_day, _)) if self.time_travel.enabled => {
+                Some(*first_day + chrono::Duration::days(self.time_travel.position as i64))
+            }
+            _ => None,
+        };
+        let shown_date = draw_data.calendar.elapsed_date();
+        draw_data.calendar.set_preview_date(preview_date);
+        draw_data.calendar.elapsed_date() != shown_date
+    }
+
+    fn ui_week_start_combobox(&mut self, ui: &mut egui::Ui) {
+        ui.label("week start:");
+        egui::ComboBox::from_id_source("weekstartcombobox")
+            .width(ui.available_width())
+            .selected_text(format!("{}", self.config.week_start))
+            .show_ui(ui, |ui| {
+                for weekday in WEEKDAYS {
+                    ui.selectable_value(
+                        &mut self.config.week_start,
+                        weekday,
+                        format!("{}", weekday),
+                    );
+                }
+            });
+    }
+
+    fn ui_weekend_days_toggles(&mut self, ui: &mut egui::Ui) {
+        ui.label("weekend days:")
+            .on_hover_text("days coloured with the weekend colour");
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 1.0;
+            let week_days: Vec<Weekday> = self.config.week_days().collect();
+            for weekday in week_days {
+                let is_weekend = self.config.is_weekend(weekday);
+                let label = format!("{}", weekday);
+                if ui
+                    .selectable_label(is_weekend, &label[..2])
+                    .on_hover_text(label)
+                    .clicked()
+                {
+                    if is_weekend {
+                        self.config.weekend_days.retain(|day| *day != weekday);
+                    } else {
+                        self.config.weekend_days.push(weekday);
+                    }
+                }
+            }
+        });
+    }
+
+    fn ui_holiday_region_combobox(&mut self, ui: &mut egui::Ui) {
+        ui.label("public holidays:");
+        egui::ComboBox::from_id_source("holidayregioncombobox")
+            .width(ui.available_width())
+            .selected_text(
+                self.config
+                    .holiday_region
+                    .map_or("none", |region| region.label()),
+            )
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.config.holiday_region, None, "none");
+                for region in HolidayRegion::ALL {
+                    ui.selectable_value(
+                        &mut self.config.holiday_region,
+                        Some(region),
+                        region.label(),
+                    );
+                }
+            });
+    }
+
     fn ui_lyfcal_config_heading(&mut self, ui: &mut egui::Ui) {
-        ui.heading("lyfcal config");
+        ui.horizontal(|ui| {
+            ui.heading("lyfcal config");
+            #[cfg(unix)]
+            if self.ipc_server.is_some()
+                && ui
+                    .button("quit")
+                    .on_hover_text("stop lyfcal, closing the window only hides it")
+                    .clicked()
+            {
+                self.quit(ui.ctx());
+            }
+        });
         egui::Grid::new("lyfcalconfigheading")
             .min_col_width(ui.available_width())
             .show(ui, |ui| {
@@ -224,6 +932,124 @@ impl LyfcalApp {
             });
     }
 
+    //Sample grid per theme, clicking one applies its colours and window look.
+    fn ui_theme_gallery(&mut self, ui: &mut egui::Ui) {
+        ui.heading("themes");
+        let themes = self
+            .theme_gallery
+            .themes
+            .get_or_insert_with(|| load_themes(&mut self.theme_gallery.status));
+        let mut chosen = None;
+        ui.horizontal_wrapped(|ui| {
+            for (index, (theme, draw_data)) in themes.iter_mut().enumerate() {
+                ui.vertical(|ui| {
+                    let (rect, response) =
+                        ui.allocate_exact_size(THEME_SAMPLE_SIZE, Sense::click());
+                    ui.painter()
+                        .rect_filled(rect, 4.0, color32(theme.window.background));
+                    //A sample the config can't lay out is left blank, the problem shows at its setting.
+                    if draw_data
+                        .update_config(&sample_config(&self.config, &theme.palette))
+                        .is_ok()
+                    {
+                        let mut sample_ui = ui.child_ui(rect.shrink(2.0), *ui.layout(), None);
+                        draw_data.draw_lyfcal(&mut sample_ui);
+                    }
+                    if theme.is_applied(&self.config) {
+                        ui.painter().rect_stroke(
+                            rect,
+                            4.0,
+                            Stroke::new(2.0, color32(theme.window.accent)),
+                        );
+                    }
+                    if response.on_hover_text("apply theme").clicked() {
+                        chosen = Some(index);
+                    }
+                    ui.label(&theme.name);
+                });
+            }
+        });
+        if let Some((theme, _)) = chosen.and_then(|index| themes.get(index)) {
+            theme.apply(&mut self.config);
+        }
+    }
+
+    fn ui_theme_export_input(&mut self, ui: &mut egui::Ui) {
+        ui.label("export theme:")
+            .on_hover_text("save the current colours as a theme in the user theme directory");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.theme_gallery.export_name)
+                    .desired_width(ui.available_width() - 60.0)
+                    .hint_text("name"),
+            );
+            let theme = Theme::from_config(self.theme_gallery.export_name.trim(), &self.config);
+            //An empty name isn't worth a complaint yet, the hint says what goes there.
+            let problem = theme.export_file_name().err();
+            let button = ui.add_enabled(problem.is_none(), egui::Button::new("export"));
+            let button = match problem {
+                Some(problem) if !theme.name.is_empty() => {
+                    button.on_disabled_hover_text(problem.to_string())
+                }
+                _ => button,
+            };
+            if button.clicked() {
+                self.theme_gallery.status = Some(
+                    save_user_theme(&theme)
+                        .map(|path| {
+                            self.theme_gallery.export_name.clear();
+                            format!("exported {}", path.display())
+                        })
+                        .map_err(|error| vec![error]),
+                );
+                self.theme_gallery.themes = None;
+            }
+        });
+    }
+
+    fn ui_theme_import_input(&mut self, ui: &mut egui::Ui) {
+        ui.label("import theme:")
+            .on_hover_text("apply a theme file and add it to the gallery");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.theme_gallery.import_path)
+                    .desired_width(ui.available_width() - 60.0)
+                    .hint_text("theme file"),
+            );
+            let path = PathBuf::from(self.theme_gallery.import_path.trim());
+            if ui
+                .add_enabled(path.is_file(), egui::Button::new("import"))
+                .clicked()
+            {
+                self.theme_gallery.status = Some(
+                    Theme::load(&path)
+                        .and_then(|theme| {
+                            theme.apply(&mut self.config);
+                            save_user_theme(&theme)
+                        })
+                        .map(|_| {
+                            self.theme_gallery.import_path.clear();
+                            format!("imported {}", path.display())
+                        })
+                        .map_err(|error| vec![error]),
+                );
+                self.theme_gallery.themes = None;
+            }
+        });
+    }
+
+    fn ui_theme_status_label(&mut self, ui: &mut egui::Ui) {
+        ui.label("");
+        match &self.theme_gallery.status {
+            Some(Ok(message)) => ui.label(message),
+            Some(Err(errors)) => {
+                let text: Vec<String> = errors.iter().map(LyfcalError::to_string).collect();
+                ui.colored_label(ui.visuals().error_fg_color, text.join("\n"))
+            }
+            None => ui.label(""),
+        };
+    }
+
     fn ui_display_config_heading(&mut self, ui: &mut egui::Ui) {
         ui.heading("display");
         egui::Grid::new("displayheading")
@@ -236,23 +1062,370 @@ impl LyfcalApp {
             });
     }
 
-    fn ui_weekday_colorpicker(&mut self, ui: &mut egui::Ui) {
-        ui.label("weekday colour:");
-        egui::Grid::new("weekdaycolorpicker")
+    fn ui_highlights_config_heading(&mut self, ui: &mut egui::Ui) {
+        ui.heading("highlights");
+        egui::Grid::new("highlightsheading")
             .min_col_width(grid_col_width(ui, 2))
             .show(ui, |ui| {
-                ui.color_edit_button_srgba(&mut self.config.color_weekday);
-                ui.color_edit_button_srgba(&mut self.config.color_weekday_elapsed);
-            });
-    }
-
+                ui.label("");
+                ui.end_row();
+                ui.label("projected");
+                ui.label("elapsed")
+            });
+    }
+
+    //One row per configured highlight with its colours and a remove button.
+    fn ui_highlight_list(&mut self, ui: &mut egui::Ui) {
+        let mut removed = None;
+        for (index, highlight) in self.config.highlights.iter_mut().enumerate() {
+            ui.label(format!("{}:", highlight.name))
+                .on_hover_text(format!("{}\nfrom {}", highlight.rule, highlight.start));
+            egui::Grid::new(("highlightcolorpicker", index))
+                .min_col_width(grid_col_width(ui, 3))
+                .show(ui, |ui| {
+                    color_edit(ui, &mut highlight.color);
+                    color_edit(ui, &mut highlight.color_elapsed);
+                    if ui.small_button("✖").on_hover_text("remove").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            ui.end_row();
+        }
+        if let Some(index) = removed {
+            self.config.highlights.remove(index);
+        }
+    }
+
+    fn ui_highlight_name_input(&mut self, ui: &mut egui::Ui) {
+        ui.label("new highlight:");
+        ui.add_sized(
+            [ui.available_width(), ui.spacing().interact_size.y],
+            egui::TextEdit::singleline(&mut self.highlight_draft.name).hint_text("payday"),
+        );
+    }
+
+    fn ui_highlight_rule_input(&mut self, ui: &mut egui::Ui) {
+        ui.label("recurrence:").on_hover_text(
+            "RRULE subset: FREQ, INTERVAL, BYDAY, BYMONTHDAY, BYSETPOS, UNTIL, COUNT",
+        );
+        ui.add_sized(
+            [ui.available_width(), ui.spacing().interact_size.y],
+            egui::TextEdit::singleline(&mut self.highlight_draft.rule)
+                .hint_text("FREQ=MONTHLY;BYDAY=1MO"),
+        );
+    }
+
+    fn ui_highlight_start_picker(&mut self, ui: &mut egui::Ui) {
+        ui.label("starting:");
+        ui.add_sized(
+            [ui.available_width(), ui.spacing().interact_size.y],
+            egui_extras::DatePickerButton::new(&mut self.highlight_draft.start)
+                .id_source("highlightstartpicker"),
+        );
+    }
+
+    fn ui_highlight_add_button(&mut self, ui: &mut egui::Ui) {
+        match &self.highlight_draft.error {
+            Some(error) => ui.colored_label(ui.visuals().error_fg_color, error),
+            None => ui.label(""),
+        };
+        ui.vertical_centered_justified(|ui| {
+            if ui.button("add highlight").clicked() {
+                match self.highlight_draft.rule.parse::<RecurrenceRule>() {
+                    Ok(rule) => {
+                        let name = match self.highlight_draft.name.trim() {
+                            "" => format!("highlight {}", self.config.highlights.len() + 1),
+                            name => name.to_string(),
+                        };
+                        self.config.highlights.push(Highlight {
+                            name,
+                            start: self.highlight_draft.start,
+                            rule,
+                            color: self.config.color_holiday,
+                            color_elapsed: self.config.color_holiday_elapsed,
+                        });
+                        self.highlight_draft.name.clear();
+                        self.highlight_draft.error = None;
+                    }
+                    Err(error) => self.highlight_draft.error = Some(error),
+                }
+            }
+        });
+    }
+
+    fn ui_widget_config_heading(&mut self, ui: &mut egui::Ui) {
+        ui.heading("widget");
+        ui.label("");
+    }
+
+    fn ui_widget_window_checkboxes(&mut self, ui: &mut egui::Ui) {
+        ui.label("window:");
+        ui.vertical(|ui| {
+            ui.checkbox(&mut self.config.enable_transparency, "transparent")
+                .on_hover_text("takes effect when the grid is next opened");
+            ui.checkbox(&mut self.config.enable_mouse_passthrough, "click-through")
+                .on_hover_text("clicks pass through the grid to the windows below");
+        });
+    }
+
+    fn ui_window_layer_combobox(&mut self, ui: &mut egui::Ui) {
+        ui.label("window layer:");
+        egui::ComboBox::from_id_source("windowlayercombobox")
+            .width(ui.available_width())
+            .selected_text(self.config.window_layer.label())
+            .show_ui(ui, |ui| {
+                for layer in WindowLayer::ALL {
+                    ui.selectable_value(&mut self.config.window_layer, layer, layer.label());
+                }
+            });
+    }
+
+    fn ui_widget_edit_mode_toggle(&mut self, ui: &mut egui::Ui) {
+        ui.label("placement:");
+        ui.horizontal(|ui| {
+            let edit_mode = self.life_viewport.edit_mode.load(Ordering::Relaxed);
+            if ui
+                .selectable_label(edit_mode, "edit")
+                .on_hover_text(format!(
+                    "show decorations and suspend click-through to move the grid ({})",
+                    ui.ctx().format_shortcut(&EDIT_MODE_SHORTCUT)
+                ))
+                .clicked()
+            {
+                self.life_viewport.toggle_edit_mode();
+                ui.ctx().request_repaint_of(LifeViewport::id());
+            }
+            if ui
+                .button("forget")
+                .on_hover_text("open maximized on every monitor again")
+                .clicked()
+            {
+                self.config.window_geometry.clear();
+            }
+        });
+    }
+
+    fn ui_wallpaper_config_heading(&mut self, ui: &mut egui::Ui) {
+        ui.heading("wallpaper");
+        ui.label("");
+    }
+
+    fn ui_wallpaper_enable_checkbox(&mut self, ui: &mut egui::Ui) {
+        ui.label("regenerate:");
+        ui.checkbox(&mut self.config.enable_wallpaper, "at midnight");
+    }
+
+    fn ui_wallpaper_path_inputs(&mut self, ui: &mut egui::Ui) {
+        ui.label("image:");
+        ui.vertical(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.config.wallpaper_path)
+                    .hint_text("output path"),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut self.config.wallpaper_background)
+                    .hint_text("background image"),
+            );
+        });
+    }
+
+    fn ui_wallpaper_command_input(&mut self, ui: &mut egui::Ui) {
+        ui.label("command:");
+        ui.add(
+            egui::TextEdit::singleline(&mut self.config.wallpaper_command)
+                .hint_text("feh --bg-fill {path}"),
+        )
+        .on_hover_text("run on the written image, which is appended when {path} isn't used");
+    }
+
+    fn ui_wallpaper_generate_button(&mut self, ui: &mut egui::Ui) {
+        match &self.wallpaper_status {
+            Some(Ok(path)) => ui.label(format!("wrote {}", path.display())),
+            Some(Err(error)) => ui.colored_label(ui.visuals().error_fg_color, error.to_string()),
+            None => ui.label(""),
+        };
+        ui.add_enabled_ui(self.wallpaper_job.is_none(), |ui| {
+            if ui.button("generate").clicked() {
+                self.generate_wallpaper(ui.ctx());
+            }
+        });
+    }
+
+    fn ui_schedules_config_heading(&mut self, ui: &mut egui::Ui) {
+        ui.heading("work schedules");
+        ui.label("");
+    }
+
+    fn ui_retirement_age_input(&mut self, ui: &mut egui::Ui) {
+        ui.label("retirement age:")
+            .on_hover_text("working days are counted up to this age");
+        ui.add_sized(
+            [ui.available_width(), ui.spacing().interact_size.y],
+            egui::DragValue::new(&mut self.config.retirement_age)
+                .range(1..=MAX_AGE)
+                .suffix(" years"),
+        );
+        self.ui_config_problems(ui, |problem| {
+            matches!(problem, LyfcalError::RetirementAge(_))
+        });
+    }
+
+    //One row per configured schedule with a remove button.
+    fn ui_schedule_list(&mut self, ui: &mut egui::Ui) {
+        let mut removed = None;
+        for (index, schedule) in self.config.work_schedules.iter().enumerate() {
+            let range = match schedule.until {
+                Some(until) => format!("{} to {}", schedule.anchor, until),
+                None => format!("from {}", schedule.anchor),
+            };
+            ui.label(format!("{}:", schedule.name)).on_hover_text(range);
+            ui.horizontal(|ui| {
+                ui.label(format_pattern(&schedule.pattern));
+                if ui.small_button("✖").on_hover_text("remove").clicked() {
+                    removed = Some(index);
+                }
+            });
+            ui.end_row();
+        }
+        if let Some(index) = removed {
+            self.config.work_schedules.remove(index);
+        }
+    }
+
+    fn ui_schedule_name_input(&mut self, ui: &mut egui::Ui) {
+        ui.label("new schedule:");
+        ui.add_sized(
+            [ui.available_width(), ui.spacing().interact_size.y],
+            egui::TextEdit::singleline(&mut self.schedule_draft.name).hint_text("employer"),
+        );
+    }
+
+    fn ui_schedule_pattern_input(&mut self, ui: &mut egui::Ui) {
+        ui.label("shift pattern:")
+            .on_hover_text("1 for an on-shift day, 0 for an off-shift day");
+        ui.vertical(|ui| {
+            egui::ComboBox::from_id_source("shiftpresetcombobox")
+                .width(ui.available_width())
+                .selected_text("preset")
+                .show_ui(ui, |ui| {
+                    for preset in ShiftPreset::ALL {
+                        if ui.selectable_label(false, preset.label()).clicked() {
+                            self.schedule_draft.pattern = preset.pattern().to_string();
+                        }
+                    }
+                });
+            ui.add_sized(
+                [ui.available_width(), ui.spacing().interact_size.y],
+                egui::TextEdit::singleline(&mut self.schedule_draft.pattern),
+            );
+        });
+    }
+
+    fn ui_schedule_range_pickers(&mut self, ui: &mut egui::Ui) {
+        ui.label("date range:")
+            .on_hover_text("the pattern cycle starts on the first date");
+        ui.vertical(|ui| {
+            ui.add_sized(
+                [ui.available_width(), ui.spacing().interact_size.y],
+                egui_extras::DatePickerButton::new(&mut self.schedule_draft.anchor)
+                    .id_source("scheduleanchorpicker"),
+            );
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.schedule_draft.has_end, "until ");
+                ui.add_enabled_ui(self.schedule_draft.has_end, |ui| {
+                    ui.add_sized(
+                        [ui.available_width(), ui.spacing().interact_size.y],
+                        egui_extras::DatePickerButton::new(&mut self.schedule_draft.until)
+                            .id_source("scheduleuntilpicker"),
+                    );
+                });
+            });
+        });
+    }
+
+    fn ui_schedule_add_button(&mut self, ui: &mut egui::Ui) {
+        match &self.schedule_draft.error {
+            Some(error) => ui.colored_label(ui.visuals().error_fg_color, error),
+            None => ui.label(""),
+        };
+        ui.vertical_centered_justified(|ui| {
+            if ui.button("add schedule").clicked() {
+                let draft = &mut self.schedule_draft;
+                let pattern = parse_pattern(&draft.pattern).and_then(|pattern| {
+                    if draft.has_end && draft.until < draft.anchor {
+                        Err("schedule ends before it starts".to_string())
+                    } else {
+                        Ok(pattern)
+                    }
+                });
+                match pattern {
+                    Ok(pattern) => {
+                        let name = match draft.name.trim() {
+                            "" => format!("schedule {}", self.config.work_schedules.len() + 1),
+                            name => name.to_string(),
+                        };
+                        self.config.work_schedules.push(WorkSchedule {
+                            name,
+                            pattern,
+                            anchor: draft.anchor,
+                            until: draft.has_end.then_some(draft.until),
+                        });
+                        draft.name.clear();
+                        draft.error = None;
+                    }
+                    Err(error) => draft.error = Some(error),
+                }
+            }
+        });
+    }
+
+    fn ui_weekday_colorpicker(&mut self, ui: &mut egui::Ui) {
+        ui.label("weekday colour:");
+        egui::Grid::new("weekdaycolorpicker")
+            .min_col_width(grid_col_width(ui, 2))
+            .show(ui, |ui| {
+                color_edit(ui, &mut self.config.color_weekday);
+                color_edit(ui, &mut self.config.color_weekday_elapsed);
+            });
+    }
+
     fn ui_weekend_colorpicker(&mut self, ui: &mut egui::Ui) {
         ui.label("weekend colour:");
         egui::Grid::new("weekendcolorpicker")
             .min_col_width(grid_col_width(ui, 2))
             .show(ui, |ui| {
-                ui.color_edit_button_srgba(&mut self.config.color_weekend);
-                ui.color_edit_button_srgba(&mut self.config.color_weekend_elapsed);
+                color_edit(ui, &mut self.config.color_weekend);
+                color_edit(ui, &mut self.config.color_weekend_elapsed);
+            });
+    }
+
+    fn ui_holiday_colorpicker(&mut self, ui: &mut egui::Ui) {
+        ui.label("holiday colour:");
+        egui::Grid::new("holidaycolorpicker")
+            .min_col_width(grid_col_width(ui, 2))
+            .show(ui, |ui| {
+                color_edit(ui, &mut self.config.color_holiday);
+                color_edit(ui, &mut self.config.color_holiday_elapsed);
+            });
+    }
+
+    fn ui_on_shift_colorpicker(&mut self, ui: &mut egui::Ui) {
+        ui.label("on shift colour:");
+        egui::Grid::new("onshiftcolorpicker")
+            .min_col_width(grid_col_width(ui, 2))
+            .show(ui, |ui| {
+                color_edit(ui, &mut self.config.color_on_shift);
+                color_edit(ui, &mut self.config.color_on_shift_elapsed);
+            });
+    }
+
+    fn ui_off_shift_colorpicker(&mut self, ui: &mut egui::Ui) {
+        ui.label("off shift colour:");
+        egui::Grid::new("offshiftcolorpicker")
+            .min_col_width(grid_col_width(ui, 2))
+            .show(ui, |ui| {
+                color_edit(ui, &mut self.config.color_off_shift);
+                color_edit(ui, &mut self.config.color_off_shift_elapsed);
             });
     }
 
@@ -261,8 +1434,8 @@ impl LyfcalApp {
         egui::Grid::new("birthdaycolorpicker")
             .min_col_width(grid_col_width(ui, 2))
             .show(ui, |ui| {
-                ui.color_edit_button_srgba(&mut self.config.color_birthday);
-                ui.color_edit_button_srgba(&mut self.config.color_birthday_elapsed);
+                color_edit(ui, &mut self.config.color_birthday);
+                color_edit(ui, &mut self.config.color_birthday_elapsed);
             });
     }
 
@@ -271,8 +1444,63 @@ impl LyfcalApp {
         egui::Grid::new("todaycolorpicker")
             .min_col_width(grid_col_width(ui, 2))
             .show(ui, |ui| {
-                ui.color_edit_button_srgba(&mut self.config.color_today);
+                color_edit(ui, &mut self.config.color_today);
+            });
+    }
+
+    //The edited colours as seen with a colour vision deficiency, and the pairs that can't be told apart.
+    fn ui_vision_preview(&mut self, ui: &mut egui::Ui) {
+        ui.label("colour vision:")
+            .on_hover_text("preview the colours as perceived with a colour vision deficiency");
+        egui::ComboBox::from_id_source("colorvisioncombobox")
+            .width(ui.available_width())
+            .selected_text(self.vision_preview.vision.label())
+            .show_ui(ui, |ui| {
+                for vision in ColorVision::ALL {
+                    ui.selectable_value(&mut self.vision_preview.vision, vision, vision.label());
+                }
             });
+        ui.end_row();
+        ui.label("");
+        let (rect, _) = ui.allocate_exact_size(
+            vec2(ui.available_width(), THEME_SAMPLE_SIZE.y),
+            Sense::hover(),
+        );
+        ui.painter()
+            .rect_filled(rect, 4.0, color32(self.config.window_visuals.background));
+        let palette = self
+            .vision_preview
+            .vision
+            .simulate_palette(&Palette::of(&self.config));
+        if self
+            .vision_preview
+            .draw_data
+            .update_config(&sample_config(&self.config, &palette))
+            .is_ok()
+        {
+            let mut sample_ui = ui.child_ui(rect.shrink(2.0), *ui.layout(), None);
+            self.vision_preview.draw_data.draw_lyfcal(&mut sample_ui);
+        }
+        //One row per kind of day, naming every colour vision it is hard to read with.
+        let warnings = contrast_warnings(&self.config);
+        let names: BTreeSet<&str> = warnings.iter().map(|warning| warning.name).collect();
+        for name in names {
+            let visions: Vec<&str> = warnings
+                .iter()
+                .filter(|warning| warning.name == name)
+                .map(|warning| warning.vision.label())
+                .collect();
+            ui.end_row();
+            ui.label("");
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "elapsed and projected {} colours look alike ({})",
+                    name,
+                    visions.join(", ")
+                ),
+            );
+        }
     }
 
     fn ui_unit_ratio_slider(&mut self, ui: &mut egui::Ui) {
@@ -281,11 +1509,20 @@ impl LyfcalApp {
         ui.add_sized(
             [ui.available_width(), ui.spacing().interact_size.y],
             egui::DragValue::new(&mut self.config.unit_ratio)
-                .range(0.1..=1.0)
+                .range(UNIT_RATIO_RANGE)
                 .speed(0.05)
                 .fixed_decimals(2)
                 .suffix("u"),
         );
+        self.ui_config_problems(ui, |problem| {
+            matches!(
+                problem,
+                LyfcalError::OutOfRange {
+                    setting: "unit ratio",
+                    ..
+                }
+            )
+        });
     }
 
     fn ui_column_spacing_slider(&mut self, ui: &mut egui::Ui) {
@@ -294,11 +1531,20 @@ impl LyfcalApp {
         ui.add_sized(
             [ui.available_width(), ui.spacing().interact_size.y],
             egui::DragValue::new(&mut self.config.col_spacing)
-                .range(0.0..=10.0)
+                .range(SPACING_RANGE)
                 .speed(0.05)
                 .fixed_decimals(2)
                 .suffix("u"),
         );
+        self.ui_config_problems(ui, |problem| {
+            matches!(
+                problem,
+                LyfcalError::OutOfRange {
+                    setting: "column spacing",
+                    ..
+                }
+            )
+        });
     }
 
     fn ui_row_spacing_slider(&mut self, ui: &mut egui::Ui) {
@@ -307,11 +1553,20 @@ impl LyfcalApp {
         ui.add_sized(
             [ui.available_width(), ui.spacing().interact_size.y],
             egui::DragValue::new(&mut self.config.row_spacing)
-                .range(0.0..=10.0)
+                .range(SPACING_RANGE)
                 .speed(0.05)
                 .fixed_decimals(2)
                 .suffix("u"),
         );
+        self.ui_config_problems(ui, |problem| {
+            matches!(
+                problem,
+                LyfcalError::OutOfRange {
+                    setting: "row spacing",
+                    ..
+                }
+            )
+        });
     }
 
     fn ui_border_spacing_slider(&mut self, ui: &mut egui::Ui) {
@@ -320,95 +1575,568 @@ impl LyfcalApp {
         ui.add_sized(
             [ui.available_width(), ui.spacing().interact_size.y],
             egui::DragValue::new(&mut self.config.border_spacing)
-                .range(0.0..=20.0)
+                .range(BORDER_SPACING_RANGE)
                 .speed(0.05)
                 .fixed_decimals(2)
                 .suffix("u"),
         );
+        self.ui_config_problems(ui, |problem| {
+            matches!(
+                problem,
+                LyfcalError::OutOfRange {
+                    setting: "border spacing",
+                    ..
+                }
+            )
+        });
+    }
+
+    fn ui_week_orientation_combobox(&mut self, ui: &mut egui::Ui) {
+        ui.label("week strips:")
+            .on_hover_text("direction the 7 days of each week are laid out");
+        egui::ComboBox::from_id_source("weekorientationcombobox")
+            .width(ui.available_width())
+            .selected_text(self.config.week_orientation.label())
+            .show_ui(ui, |ui| {
+                for orientation in WeekOrientation::ALL {
+                    ui.selectable_value(
+                        &mut self.config.week_orientation,
+                        orientation,
+                        orientation.label(),
+                    );
+                }
+            });
+    }
+
+    fn ui_flow_direction_comboboxes(&mut self, ui: &mut egui::Ui) {
+        ui.label("flow direction:");
+        egui::Grid::new("flowdirectiongrid")
+            .min_col_width(grid_col_width(ui, 2))
+            .show(ui, |ui| {
+                egui::ComboBox::from_id_source("horizontalflowcombobox")
+                    .width(grid_col_width(ui, 2))
+                    .selected_text(self.config.horizontal_flow.label())
+                    .show_ui(ui, |ui| {
+                        for flow in HorizontalFlow::ALL {
+                            ui.selectable_value(
+                                &mut self.config.horizontal_flow,
+                                flow,
+                                flow.label(),
+                            );
+                        }
+                    });
+                egui::ComboBox::from_id_source("verticalflowcombobox")
+                    .width(ui.available_width())
+                    .selected_text(self.config.vertical_flow.label())
+                    .show_ui(ui, |ui| {
+                        for flow in VerticalFlow::ALL {
+                            ui.selectable_value(&mut self.config.vertical_flow, flow, flow.label());
+                        }
+                    });
+            });
+    }
+
+    fn ui_flow_mode_combobox(&mut self, ui: &mut egui::Ui) {
+        ui.label("flow mode:")
+            .on_hover_text("order in which weeks fill the columns");
+        egui::ComboBox::from_id_source("flowmodecombobox")
+            .width(ui.available_width())
+            .selected_text(self.config.flow_mode.label())
+            .show_ui(ui, |ui| {
+                for mode in FlowMode::ALL {
+                    ui.selectable_value(&mut self.config.flow_mode, mode, mode.label());
+                }
+            });
+    }
+
+    fn ui_grid_sizing_combobox(&mut self, ui: &mut egui::Ui) {
+        ui.label("grid sizing:")
+            .on_hover_text("what is held fixed while fitting the grid to the screen");
+        egui::ComboBox::from_id_source("gridsizingcombobox")
+            .width(ui.available_width())
+            .selected_text(self.config.grid_sizing.label())
+            .show_ui(ui, |ui| {
+                for sizing in GridSizing::ALL {
+                    ui.selectable_value(&mut self.config.grid_sizing, sizing, sizing.label());
+                }
+            });
+        //Only the constraint of the chosen sizing is editable.
+        match self.config.grid_sizing {
+            GridSizing::Fit => {}
+            GridSizing::FixedUnitSize => {
+                ui.end_row();
+                ui.label("unit size:")
+                    .on_hover_text("days that don't fit the screen are hidden");
+                ui.add_sized(
+                    [ui.available_width(), ui.spacing().interact_size.y],
+                    egui::DragValue::new(&mut self.config.unit_size)
+                        .range(UNIT_SIZE_RANGE)
+                        .speed(0.1)
+                        .fixed_decimals(1)
+                        .suffix("pt"),
+                );
+                self.ui_config_problems(ui, |problem| {
+                    matches!(
+                        problem,
+                        LyfcalError::OutOfRange {
+                            setting: "unit size",
+                            ..
+                        }
+                    )
+                });
+            }
+            GridSizing::FixedColumns => {
+                ui.end_row();
+                ui.label("columns:")
+                    .on_hover_text("a column represents 7 days/units");
+                ui.add_sized(
+                    [ui.available_width(), ui.spacing().interact_size.y],
+                    egui::DragValue::new(&mut self.config.column_count)
+                        .range(COLUMN_COUNT_RANGE)
+                        .speed(0.1),
+                );
+                self.ui_config_problems(ui, |problem| {
+                    matches!(
+                        problem,
+                        LyfcalError::OutOfRange {
+                            setting: "column count",
+                            ..
+                        }
+                    )
+                });
+            }
+        }
+    }
+
+    fn ui_grid_alignment_combobox(&mut self, ui: &mut egui::Ui) {
+        ui.label("grid alignment:")
+            .on_hover_text("where the grid goes in the space it leaves over");
+        egui::ComboBox::from_id_source("gridalignmentcombobox")
+            .width(ui.available_width())
+            .selected_text(self.config.grid_alignment.label())
+            .show_ui(ui, |ui| {
+                for alignment in GridAlignment::ALL {
+                    ui.selectable_value(
+                        &mut self.config.grid_alignment,
+                        alignment,
+                        alignment.label(),
+                    );
+                }
+            });
+    }
+
+    //Metrics of the grid as last drawn, so they follow the applied rather than the edited config.
+    fn ui_grid_fit_label(&self, ui: &mut egui::Ui) {
+        ui.label("grid fit:").on_hover_text(
+            "share of the screen covered by days, and the space left around the grid",
+        );
+        match self.life_viewport.draw_data().layout_metrics() {
+            Some(metrics) => {
+                let mut text = format!(
+                    "{:.0}% filled, {:.0}pt² unused",
+                    metrics.fill_ratio * 100.0,
+                    metrics.wasted_area
+                );
+                if metrics.hidden_days > 0 {
+                    text += &format!(", {} days hidden", metrics.hidden_days);
+                }
+                ui.label(text)
+                    .on_hover_text(format!("{} empty week slots", metrics.empty_slots));
+            }
+            None => {
+                ui.label("not drawn yet");
+            }
+        }
     }
 }
 
 //================================================== EFRAME IMPLEMENTATION ==================================================
 
+impl LyfcalApp {
+    //Render the wallpaper on a worker thread, since large screens take a moment to rasterize.
+    fn generate_wallpaper(&mut self, ctx: &egui::Context) {
+        if self.wallpaper_job.is_some() {
+            return;
+        }
+        let Some(screen) = self.screen else {
+            self.wallpaper_status = Some(Err(LyfcalError::UnknownScreenSize));
+            return;
+        };
+        let config = self.config.clone();
+        let ctx = ctx.clone();
+        self.wallpaper_job = Some(std::thread::spawn(move || {
+            let result = super::wallpaper::generate(&config, screen);
+            ctx.request_repaint();
+            result
+        }));
+    }
+
+    fn poll_wallpaper_job(&mut self) {
+        if self
+            .wallpaper_job
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+        {
+            if let Some(job) = self.wallpaper_job.take() {
+                self.wallpaper_status =
+                    Some(job.join().unwrap_or(Err(LyfcalError::WallpaperPanicked)));
+            }
+        }
+    }
+
+    fn life_viewport_builder(&self) -> egui::ViewportBuilder {
+        let edit_mode = self.life_viewport.edit_mode.load(Ordering::Relaxed);
+        let builder = egui::ViewportBuilder::default()
+            .with_title("lyfcal")
+            .with_min_inner_size([480.0, 320.0])
+            .with_transparent(self.config.enable_transparency)
+            .with_decorations(edit_mode)
+            .with_mouse_passthrough(self.config.enable_mouse_passthrough && !edit_mode)
+            .with_window_level(window_level(self.config.window_layer))
+            .with_fullsize_content_view(true);
+        match self.opened_geometry {
+            Some(geometry) => builder
+                .with_position([geometry.position.x, geometry.position.y])
+                .with_inner_size([geometry.inner_size.x, geometry.inner_size.y]),
+            None => builder.with_maximized(true),
+        }
+    }
+}
+
 impl eframe::App for LyfcalApp {
     fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
         egui::Rgba::TRANSPARENT.to_array()
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        //Follow the local date into the life viewport when it is set to now.
+        let mut viewport_changed = false;
+        let rolled_over = self.clock.poll();
+        if let Some(today) = rolled_over {
+            if self.config.elapsed_date_bool {
+                self.config.elapsed_date = today;
+            }
+            let mut draw_data = self.life_viewport.draw_data_mut();
+            if draw_data.calendar.config.elapsed_date_bool {
+                draw_data.calendar.set_elapsed_date(today);
+                viewport_changed = true;
+            }
+        }
+        #[cfg(unix)]
+        {
+            self.handle_ipc_requests(ctx);
+            self.hide_on_close(ctx);
+        }
+        let screen = ctx.input(|i| {
+            let viewport = i.viewport();
+            Some(Screen {
+                size: viewport.monitor_size?,
+                pixels_per_point: viewport.native_pixels_per_point?,
+            })
+        });
+        self.screen = screen.or(self.screen);
+        if rolled_over.is_some() && self.config.enable_wallpaper {
+            self.generate_wallpaper(ctx);
+        }
+        self.poll_wallpaper_job();
+        if ctx.input_mut(|i| i.consume_shortcut(&EDIT_MODE_SHORTCUT)) {
+            self.life_viewport.toggle_edit_mode();
+            viewport_changed = true;
+        }
+        if let Some((monitor, geometry)) = self.life_viewport.geometry().take() {
+            self.config.window_geometry.insert(monitor, geometry);
+        }
+        //egui only repaints on input, so the scheduled repaints are for the date to roll over at midnight
+        //and for the hourly frame report.
+        ctx.request_repaint_after(self.clock.time_until_rollover().min(self.report_frames()));
+        if self.config.live_preview {
+            viewport_changed |= self.apply_config() != ConfigChange::None;
+        }
+        viewport_changed |= self.update_time_travel(ctx);
+        self.save_config(ctx);
+        self.config_frames.record();
+        let visuals = egui_visuals(&self.config.window_visuals);
+        if ctx.style().visuals != visuals {
+            ctx.set_visuals(visuals);
+        }
+
         egui::CentralPanel::default()
             //.frame(egui::Frame::none())
             .show(ctx, |ui| {
-                self.draw_config_ui(ui);
-                ui.add_space(20.0);
-                self.debug_println(ui)
-            });
-
-        if self.show_immediate_viewport {
-            ctx.show_viewport_immediate(
-                egui::ViewportId::from_hash_of("immediate_viewport"),
-                egui::ViewportBuilder::default()
-                    .with_title("lyfcal")
-                    .with_min_inner_size([480.0, 320.0])
-                    .with_transparent(true)
-                    .with_maximized(true)
-                    .with_decorations(false)
-                    //.with_mouse_passthrough(true)
-                    .with_fullsize_content_view(true),
-                |ctx, class| {
-                    assert!(
-                        class == egui::ViewportClass::Immediate,
-                        "This egui backend doesn't support multiple viewports"
-                    );
-
-                    egui::CentralPanel::default()
-                        .frame(egui::Frame::none())
-                        .show(ctx, |ui| self.draw_data.draw_lyfcal(ui));
-
-                    //close viewport
-                    if ctx.input(|i| i.viewport().close_requested()) {
-                        self.show_immediate_viewport = false;
-                    }
-                },
-            );
-        }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.draw_config_ui(ui);
+                    ui.add_space(20.0);
+                    #[cfg(debug_assertions)]
+                    self.debug_println(ui)
+                });
+            });
 
-        /* For defferred viewport.
-        if self.show_deferred_viewport.load(Ordering::Relaxed) {
-            let show_deferred_viewport = self.show_deferred_viewport.clone();
+        if self.life_viewport.open.load(Ordering::Relaxed) {
+            if viewport_changed {
+                ctx.request_repaint_of(LifeViewport::id());
+            }
+            let life_viewport = self.life_viewport.clone();
+            let transparent = self.config.enable_transparency;
             ctx.show_viewport_deferred(
-                egui::ViewportId::from_hash_of("deferred_viewport"),
-                egui::ViewportBuilder::default()
-                    .with_title("lyfcal")
-                    .with_min_inner_size([480.0, 320.0])
-                    .with_transparent(true)
-                    .with_maximized(true)
-                    .with_decorations(false)
-                    //.with_mouse_passthrough(true)
-                    .with_fullsize_content_view(true),
+                LifeViewport::id(),
+                self.life_viewport_builder(),
                 move |ctx, class| {
                     assert!(
                         class == egui::ViewportClass::Deferred,
                         "This egui backend doesn't support multiple viewports"
                     );
 
-                    egui::CentralPanel::default().show(ctx, |ui| {
-                        for days in self.draw_data.events {
-                            ui.label(format!("{:?}", days));
+                    life_viewport.frames().record();
+                    let fill = if transparent {
+                        Color32::TRANSPARENT
+                    } else {
+                        ctx.style().visuals.panel_fill
+                    };
+                    egui::CentralPanel::default()
+                        .frame(egui::Frame::none().fill(fill))
+                        .show(ctx, |ui| life_viewport.draw_data_mut().draw_lyfcal(ui));
+
+                    //The shortcut also works from the grid while it isn't click-through.
+                    if ctx.input_mut(|i| i.consume_shortcut(&EDIT_MODE_SHORTCUT)) {
+                        life_viewport.toggle_edit_mode();
+                        ctx.request_repaint_of(egui::ViewportId::ROOT);
+                    }
+
+                    //Remember where the grid was placed on this monitor. A maximized or fullscreen
+                    //window covers the screen rather than being placed, so it isn't remembered.
+                    let placement = ctx.input(|i| {
+                        let viewport = i.viewport();
+                        if viewport.maximized == Some(true)
+                            || viewport.fullscreen == Some(true)
+                            || viewport.minimized == Some(true)
+                        {
+                            return None;
                         }
+                        Some((
+                            viewport.monitor_size?,
+                            viewport.outer_rect?,
+                            viewport.inner_rect?,
+                        ))
                     });
+                    if let Some((monitor_size, outer_rect, inner_rect)) = placement {
+                        let monitor = monitor(outer_rect, monitor_size);
+                        let geometry = WindowGeometry {
+                            position: lyfcal_core::geometry::pos2(
+                                outer_rect.min.x,
+                                outer_rect.min.y,
+                            ),
+                            inner_size: lyfcal_core::geometry::vec2(
+                                inner_rect.width(),
+                                inner_rect.height(),
+                            ),
+                        };
+                        if life_viewport.report_geometry((monitor, geometry)) {
+                            ctx.request_repaint_of(egui::ViewportId::ROOT);
+                        }
+                    }
+
+                    //close viewport
                     if ctx.input(|i| i.viewport().close_requested()) {
-                        show_deferred_viewport.store(false, Ordering::Relaxed);
+                        life_viewport.open.store(false, Ordering::Relaxed);
+                        ctx.request_repaint_of(egui::ViewportId::ROOT);
                     }
                 },
             );
         }
-        */
+    }
+}
+
+//================================================== INSTRUMENTATION ==================================================
+
+//Counts rendered frames in one minute buckets over a rolling hour.
+#[derive(Debug)]
+struct FrameCounter {
+    start: Instant,
+    buckets: VecDeque<(u64, u32)>,
+}
+
+impl Default for FrameCounter {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            buckets: VecDeque::new(),
+        }
+    }
+}
+
+impl FrameCounter {
+    fn record(&mut self) {
+        let minute = self.start.elapsed().as_secs() / 60;
+        match self.buckets.back_mut() {
+            Some((last_minute, count)) if *last_minute == minute => *count += 1,
+            _ => self.buckets.push_back((minute, 1)),
+        }
+        while self
+            .buckets
+            .front()
+            .is_some_and(|(first_minute, _)| minute - first_minute >= 60)
+        {
+            self.buckets.pop_front();
+        }
+    }
+
+    fn frames_last_hour(&self) -> u32 {
+        let minute = self.start.elapsed().as_secs() / 60;
+        self.buckets
+            .iter()
+            .filter(|(bucket_minute, _)| minute - bucket_minute < 60)
+            .map(|(_, count)| count)
+            .sum()
     }
 }
 
 //================================================== UI FUNCTIONS ==================================================
 
+//Fonts in the assets' fonts directory are tried before egui's own, in file name order.
+pub fn install_fonts(ctx: &egui::Context) {
+    let mut fonts = FontDefinitions::default();
+    let mut installed = 0;
+    for path in super::assets::list("fonts") {
+        let font = super::assets::load(&path).and_then(|bytes| {
+            //egui panics on fonts it can't parse, so they are checked first.
+            match ab_glyph::FontRef::try_from_slice(&bytes) {
+                Ok(_) => Ok(bytes.into_owned()),
+                Err(error) => Err(LyfcalError::Parse {
+                    path: path.clone().into(),
+                    message: error.to_string(),
+                }),
+            }
+        });
+        match font {
+            Ok(bytes) => {
+                fonts
+                    .font_data
+                    .insert(path.clone(), FontData::from_owned(bytes));
+                for family in [FontFamily::Proportional, FontFamily::Monospace] {
+                    fonts
+                        .families
+                        .entry(family)
+                        .or_default()
+                        .insert(installed, path.clone());
+                }
+                installed += 1;
+            }
+            Err(error) => eprintln!("lyfcal: {}", error),
+        }
+    }
+    if installed > 0 {
+        ctx.set_fonts(fonts);
+    }
+}
+
+const EDIT_MODE_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::CTRL.plus(Modifiers::SHIFT), Key::E);
+
+//Monitor a window is on, found from the window's centre.
+fn monitor(outer_rect: egui::Rect, monitor_size: Vec2) -> Monitor {
+    let center = outer_rect.center();
+    Monitor::containing(
+        lyfcal_core::geometry::pos2(center.x, center.y),
+        lyfcal_core::geometry::vec2(monitor_size.x, monitor_size.y),
+    )
+}
+
+fn window_level(layer: WindowLayer) -> egui::WindowLevel {
+    match layer {
+        WindowLayer::Below => egui::WindowLevel::AlwaysOnBottom,
+        WindowLayer::Normal => egui::WindowLevel::Normal,
+        WindowLayer::Above => egui::WindowLevel::AlwaysOnTop,
+    }
+}
+
+//Toolkit style for a theme's window look, egui's own dark or light style recoloured.
+fn egui_visuals(window: &WindowVisuals) -> egui::Visuals {
+    let mut visuals = if window.dark_mode {
+        egui::Visuals::dark()
+    } else {
+        egui::Visuals::light()
+    };
+    visuals.panel_fill = color32(window.background);
+    visuals.window_fill = color32(window.background);
+    visuals.widgets.noninteractive.fg_stroke.color = color32(window.text);
+    visuals.selection.bg_fill = color32(window.accent);
+    visuals
+}
+
+//Readable theme assets, built in themes first in their usual order. Those that can't be read are reported in the status.
+fn load_themes(status: &mut Option<Result<String, Vec<LyfcalError>>>) -> Vec<(Theme, DrawData)> {
+    let mut themes = Vec::new();
+    let mut problems = Vec::new();
+    for path in assets::list(assets::THEME_DIR) {
+        match assets::load(&path).and_then(|bytes| Theme::parse(&bytes, Path::new(&path))) {
+            Ok(theme) => themes.push(theme),
+            Err(error) => problems.push(error),
+        }
+    }
+    if !problems.is_empty() {
+        *status = Some(Err(problems));
+    }
+    let builtin: Vec<String> = Theme::builtin()
+        .into_iter()
+        .map(|theme| theme.file_name())
+        .collect();
+    themes.sort_by_key(|theme| {
+        builtin
+            .iter()
+            .position(|file_name| *file_name == theme.file_name())
+            .unwrap_or(builtin.len())
+    });
+    themes
+        .into_iter()
+        .map(|theme| (theme, DrawData::default()))
+        .collect()
+}
+
+//Write a theme to the user theme directory, returning where it went.
+fn save_user_theme(theme: &Theme) -> Result<PathBuf, LyfcalError> {
+    let path = assets::override_dir()
+        .ok_or(LyfcalError::NoDataDirectory)?
+        .join(assets::THEME_DIR)
+        .join(theme.export_file_name()?);
+    theme.save(&path)?;
+    Ok(path)
+}
+
+//Half of a one year lifespan lived, laid out like the edited config and in the palette's colours.
+fn sample_config(
+    config: &lyfcal_core::config::Config,
+    palette: &Palette,
+) -> lyfcal_core::config::Config {
+    let mut sample = lyfcal_core::config::Config {
+        birthdate: config.elapsed_date.checked_sub_days(Days::new(182)),
+        life_expectancy: 1,
+        elapsed_date_bool: false,
+        notes: Default::default(),
+        grid_sizing: GridSizing::Fit,
+        ..config.clone()
+    };
+    palette.apply(&mut sample);
+    sample
+}
+
+//Colour picker for a config colour, which is stored without egui's types.
+fn color_edit(ui: &mut Ui, color: &mut Color) -> Response {
+    let mut color32 = color32(*color);
+    let response = ui.color_edit_button_srgba(&mut color32);
+    *color = color_from_egui(color32);
+    response
+}
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
 fn grid_col_width(ui: &egui::Ui, n_col: usize) -> f32 {
     let gap_space = ui.spacing().item_spacing.x * (n_col as f32 - 1.0);
     let grid_w = ui.available_width();
@@ -0,0 +1,378 @@
+use chrono::NaiveDate;
+use lyfcal_core::error::LyfcalError;
+use std::fs::{DirBuilder, File, Permissions, TryLockError};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+//How long a forwarding invocation waits for the running instance to handle its command.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+//How long an invocation that lost the race for the socket waits for the winner to listen on it.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub const USAGE: &str = "usage: lyfcal [command]
+
+commands:
+    config                      open the config window (default)
+    show                        show the life grid
+    note <yyyy-mm-dd> <text>    add a note to a day
+    reload                      reload the saved config
+    quit                        stop the running instance, closing its window only hides it
+    status [options]            print life progress for a status bar
+        --format <text|waybar>  output plain text or waybar json (default text)
+        --template <template>   text using {percent} {lived} {remaining} {bar} {glyph}
+                                {milestone} and {events} (default \"{bar} {percent}%\")
+        --watch                 print again at every midnight
+    tui                         show the life grid in the terminal
+    prompt [options]            print a short segment for a shell prompt
+        --template <template>   as for status (default \"{glyph} {percent}%\")
+        init <shell>            print the bash, zsh, fish or starship setup
+    help                        print this message
+
+files in lyfcal/assets under the user data directory ($XDG_DATA_HOME on Linux) replace
+the built in assets, i.e. icon.png, fonts/*.ttf are used before the built in fonts and
+themes/*.json add to the built in themes or replace those of the same file name";
+
+//Request handled by the single running instance, forwarded from later invocations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    OpenConfig,
+    ShowViewport,
+    AddNote { date: NaiveDate, text: String },
+    Reload,
+    Quit,
+}
+
+impl Command {
+    //Parse the command line arguments, without the program name.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, LyfcalError> {
+        let args: Vec<String> = args.into_iter().collect();
+        if args.is_empty() {
+            return Ok(Self::OpenConfig);
+        }
+        args.join(" ").parse()
+    }
+}
+
+//Commands travel as a single line each, in the same form they are typed on the command line.
+impl std::str::FromStr for Command {
+    type Err = LyfcalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, rest) = s.split_once(' ').unwrap_or((s, ""));
+        match name {
+            "config" => Ok(Self::OpenConfig),
+            "show" => Ok(Self::ShowViewport),
+            "reload" => Ok(Self::Reload),
+            "quit" => Ok(Self::Quit),
+            "note" => {
+                let (date, text) = rest.trim().split_once(' ').unwrap_or((rest.trim(), ""));
+                let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                    LyfcalError::Usage(format!("invalid date \"{}\", use yyyy-mm-dd", date))
+                })?;
+                let text = text.trim();
+                if text.is_empty() {
+                    return Err(LyfcalError::Usage("note text is empty".to_string()));
+                }
+                Ok(Self::AddNote {
+                    date,
+                    text: text.to_string(),
+                })
+            }
+            "" => Err(LyfcalError::Usage("missing command".to_string())),
+            _ => Err(LyfcalError::Usage(format!("unknown command \"{}\"", name))),
+        }
+    }
+}
+
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OpenConfig => write!(f, "config"),
+            Self::ShowViewport => write!(f, "show"),
+            //Line breaks would end the request early.
+            Self::AddNote { date, text } => {
+                write!(
+                    f,
+                    "note {} {}",
+                    date.format("%Y-%m-%d"),
+                    text.replace('\n', " ")
+                )
+            }
+            Self::Reload => write!(f, "reload"),
+            Self::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+//Command received by the running instance, to be answered once the app has handled it.
+#[derive(Debug)]
+pub struct Request {
+    pub command: Command,
+    reply: Sender<Result<(), LyfcalError>>,
+}
+
+impl Request {
+    pub fn reply(self, result: Result<(), LyfcalError>) {
+        //The client may have given up waiting, which is fine.
+        let _ = self.reply.send(result);
+    }
+}
+
+//================================================== SOCKET ==================================================
+
+//Run after each request is passed on, so the app can wake up to handle it.
+type Waker = Box<dyn Fn() + Send + Sync>;
+
+//Receiving end of the socket, removed again when the running instance exits.
+pub struct Server {
+    path: PathBuf,
+    requests: Receiver<Request>,
+    waker: Arc<OnceLock<Waker>>,
+    //Held for as long as the instance runs, whoever holds it owns the socket.
+    _lock: File,
+}
+
+impl Server {
+    pub fn try_recv(&self) -> Option<Request> {
+        self.requests.try_recv().ok()
+    }
+
+    //Requests that come in before there is a waker wait for the app to look for them.
+    pub fn wake_with(&self, waker: impl Fn() + Send + Sync + 'static) {
+        let _ = self.waker.set(Box::new(waker));
+    }
+}
+
+impl std::fmt::Debug for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Server").field("path", &self.path).finish()
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+//Socket in the user's runtime directory, so each user gets their own instance.
+//Without one it goes in a directory of the user's cache only they can enter, not in a world writable one.
+pub fn socket_path() -> std::io::Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir).join("lyfcal.sock"));
+    }
+    let dir = dirs::cache_dir()
+        .ok_or_else(|| std::io::Error::new(ErrorKind::NotFound, "no runtime or cache directory"))?
+        .join("lyfcal");
+    DirBuilder::new().recursive(true).mode(0o700).create(&dir)?;
+    //A directory that already existed keeps its mode otherwise.
+    std::fs::set_permissions(&dir, Permissions::from_mode(0o700))?;
+    Ok(dir.join("lyfcal.sock"))
+}
+
+//Hand the command to a running instance. Fails with the connection error if there is none.
+pub fn forward(command: &Command) -> std::io::Result<Result<(), LyfcalError>> {
+    let mut stream = UnixStream::connect(socket_path()?)?;
+    //Leave the running instance time to give up on the app and say so.
+    stream.set_read_timeout(Some(REPLY_TIMEOUT * 2))?;
+    writeln!(stream, "{}", command)?;
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    Ok(parse_response(&response))
+}
+
+//Hand the command to an instance that is starting up, waiting for it to listen.
+pub fn forward_once_listening(command: &Command) -> std::io::Result<Result<(), LyfcalError>> {
+    let start = std::time::Instant::now();
+    loop {
+        match forward(command) {
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    ErrorKind::NotFound | ErrorKind::ConnectionRefused
+                ) && start.elapsed() < STARTUP_TIMEOUT =>
+            {
+                std::thread::sleep(STARTUP_POLL_INTERVAL)
+            }
+            result => return result,
+        }
+    }
+}
+
+//Become the running instance, taking over the socket left behind by one that exited without cleaning up.
+//Fails with AddrInUse while another instance is running or starting. Commands are passed to the returned server.
+pub fn listen() -> std::io::Result<Server> {
+    let path = socket_path()?;
+    //Two instances starting together would otherwise both find the socket stale and remove each other's.
+    let lock = File::create(path.with_extension("lock"))?;
+    lock.try_lock().map_err(|error| match error {
+        TryLockError::WouldBlock => {
+            std::io::Error::new(ErrorKind::AddrInUse, "another instance is starting")
+        }
+        TryLockError::Error(error) => error,
+    })?;
+    if path.exists() && UnixStream::connect(&path).is_err() {
+        std::fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    let (sender, requests) = mpsc::channel();
+    let waker = Arc::new(OnceLock::<Waker>::new());
+    let server = Server {
+        path,
+        requests,
+        waker: waker.clone(),
+        _lock: lock,
+    };
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            //A thread per client, so one that never sends its line holds up no one else.
+            let (sender, waker) = (sender.clone(), waker.clone());
+            std::thread::spawn(move || {
+                //A client that disconnects early only loses its own reply.
+                let _ = handle_client(stream, &sender, &waker);
+            });
+        }
+    });
+    Ok(server)
+}
+
+fn handle_client(
+    stream: UnixStream,
+    sender: &Sender<Request>,
+    waker: &OnceLock<Waker>,
+) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let result = match line.parse::<Command>() {
+        Ok(command) => {
+            let (reply, response) = mpsc::channel();
+            if sender.send(Request { command, reply }).is_err() {
+                return Ok(());
+            }
+            if let Some(wake) = waker.get() {
+                wake();
+            }
+            response
+                .recv_timeout(REPLY_TIMEOUT)
+                .unwrap_or(Err(LyfcalError::NoResponse))
+        }
+        Err(error) => Err(error),
+    };
+    let mut stream = &stream;
+    writeln!(stream, "{}", format_response(&result))
+}
+
+fn format_response(result: &Result<(), LyfcalError>) -> String {
+    match result {
+        Ok(()) => "ok".to_string(),
+        Err(error) => format!("error {}", error.to_string().replace('\n', " ")),
+    }
+}
+
+//Only the text of an error makes it across, so the forwarding side gets it back as a remote error.
+fn parse_response(response: &str) -> Result<(), LyfcalError> {
+    match response.trim() {
+        "ok" => Ok(()),
+        response => match response.strip_prefix("error ") {
+            Some(message) => Err(LyfcalError::Remote(message.to_string())),
+            None => Err(LyfcalError::NoResponse),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_round_trip_as_text() {
+        let commands = [
+            Command::OpenConfig,
+            Command::ShowViewport,
+            Command::Reload,
+            Command::Quit,
+            Command::AddNote {
+                date: NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+                text: "first day at the new job".to_string(),
+            },
+        ];
+        for command in commands {
+            assert_eq!(command.to_string().parse(), Ok(command));
+        }
+    }
+
+    #[test]
+    fn note_text_stays_on_one_line() {
+        let command = Command::AddNote {
+            date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+            text: "two\nlines".to_string(),
+        };
+        assert_eq!(command.to_string(), "note 2024-06-01 two lines");
+    }
+
+    #[test]
+    fn arguments_are_parsed_as_commands() {
+        let args = |args: &[&str]| Command::from_args(args.iter().map(|arg| arg.to_string()));
+        assert_eq!(args(&[]), Ok(Command::OpenConfig));
+        assert_eq!(
+            args(&["note", "2024-06-01", "moved", "house"]),
+            Ok(Command::AddNote {
+                date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+                text: "moved house".to_string(),
+            })
+        );
+        assert!(args(&["note", "2024-06-01"]).is_err());
+        assert!(args(&["note", "01/06/2024", "moved"]).is_err());
+        assert!(args(&["frobnicate"]).is_err());
+    }
+
+    #[test]
+    fn responses_round_trip() {
+        let remote = |message: &str| Err(LyfcalError::Remote(message.to_string()));
+        assert_eq!(parse_response(&format_response(&Ok(()))), Ok(()));
+        assert_eq!(
+            parse_response(&format_response(&Err(LyfcalError::NoResponse))),
+            remote("lyfcal didn't respond")
+        );
+        assert_eq!(
+            parse_response(&format_response(&Err(LyfcalError::Usage(
+                "two\nlines".to_string()
+            )))),
+            remote("two lines")
+        );
+        //A running instance that hangs up without answering.
+        assert_eq!(parse_response(""), Err(LyfcalError::NoResponse));
+        assert_eq!(parse_response("ok\n"), Ok(()));
+    }
+
+    #[test]
+    fn second_instance_is_refused_and_forwards_to_the_first() {
+        //The only test that touches the socket, so the runtime directory is set for it alone.
+        let dir = std::env::temp_dir().join(format!("lyfcal-ipc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::env::set_var("XDG_RUNTIME_DIR", &dir);
+
+        let server = listen().unwrap();
+        assert_eq!(listen().unwrap_err().kind(), ErrorKind::AddrInUse);
+        let (woken, wake) = mpsc::channel();
+        server.wake_with(move || woken.send(()).unwrap());
+        let client = std::thread::spawn(|| forward_once_listening(&Command::Reload));
+        wake.recv_timeout(REPLY_TIMEOUT).unwrap();
+        let request = server.try_recv().unwrap();
+        assert_eq!(request.command, Command::Reload);
+        request.reply(Ok(()));
+        assert_eq!(client.join().unwrap().unwrap(), Ok(()));
+
+        drop(server);
+        assert!(!dir.join("lyfcal.sock").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,37 +2,159 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use egui::IconData;
+use lyfcal_core::error::LyfcalError;
 
-mod config;
+mod assets;
 mod draw;
 mod gui;
+#[cfg(unix)]
+mod ipc;
+mod prompt;
+mod status;
+mod tui;
+mod wallpaper;
 
 fn main() -> Result<(), eframe::Error> {
-    let icon = image::open("src/assets/icon.png")
-        .expect("Failed to open icon path")
-        .to_rgba8();
-    let (icon_width, icon_height) = icon.dimensions();
-    let icon_data = IconData {
-        rgba: icon.into_raw(),
-        width: icon_width,
-        height: icon_height,
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    //Status output for bars runs on its own, without a window or the running instance.
+    if args.first().map(String::as_str) == Some("status") {
+        let result = status::StatusOptions::from_args(args.into_iter().skip(1))
+            .and_then(|options| status::run(&options));
+        if let Err(error) = result {
+            eprintln!("lyfcal: {}", error);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if args.first().map(String::as_str) == Some("prompt") {
+        let result = prompt::PromptOptions::from_args(args.into_iter().skip(1))
+            .and_then(|options| prompt::run(&options, &lyfcal_core::clock::SystemClock));
+        if let Err(error) = result {
+            eprintln!("lyfcal: {}", error);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if args.first().map(String::as_str) == Some("tui") {
+        if let Err(error) = tui::run() {
+            eprintln!("lyfcal: {}", error);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    //Only one instance runs per user, later invocations hand their command to it and exit.
+    #[cfg(unix)]
+    let (command, server) = {
+        if matches!(
+            args.first().map(String::as_str),
+            Some("help" | "--help" | "-h")
+        ) {
+            println!("{}", ipc::USAGE);
+            return Ok(());
+        }
+        let command = ipc::Command::from_args(args).unwrap_or_else(|error| {
+            eprintln!("lyfcal: {}\n\n{}", error, ipc::USAGE);
+            std::process::exit(2)
+        });
+        if let Ok(result) = ipc::forward(&command) {
+            exit_forwarded(result);
+        }
+        if command == ipc::Command::Quit {
+            eprintln!("lyfcal: no running instance to quit");
+            std::process::exit(1);
+        }
+        let server = match ipc::listen() {
+            Ok(server) => Some(server),
+            //Another instance took the socket first or is about to, so the command goes to it instead of a second window.
+            Err(error) if error.kind() == std::io::ErrorKind::AddrInUse => {
+                match ipc::forward_once_listening(&command) {
+                    Ok(result) => exit_forwarded(result),
+                    Err(error) => {
+                        eprintln!(
+                            "lyfcal: another instance is running but can't be reached: {}",
+                            error
+                        );
+                        std::process::exit(1);
+                    }
+                }
+            }
+            Err(error) => {
+                eprintln!("lyfcal: not accepting commands: {}", error);
+                None
+            }
+        };
+        (command, server)
     };
 
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_title("lyfcal")
+        .with_maximize_button(false)
+        .with_inner_size([345.0, 470.0])
+        //.with_transparent(true)
+        .with_resizable(false)
+        .with_always_on_top();
+    //A user supplied icon that can't be decoded falls back to the embedded one.
+    let icon = assets::load(assets::ICON)
+        .and_then(|bytes| decode_icon(&bytes))
+        .or_else(|error| {
+            eprintln!("lyfcal: {}, using the built in icon", error);
+            decode_icon(assets::embedded(assets::ICON).unwrap_or_default())
+        });
+    match icon {
+        Ok(icon) => viewport = viewport.with_icon(icon),
+        Err(error) => eprintln!("lyfcal: {}", error),
+    }
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_title("lyfcal")
-            .with_maximize_button(false)
-            .with_icon(icon_data)
-            .with_inner_size([345.0, 470.0])
-            //.with_transparent(true)
-            .with_resizable(false)
-            .with_always_on_top(),
+        viewport,
         ..Default::default()
     };
 
     eframe::run_native(
         "lyfcal config",
         options,
-        Box::new(|_cc| Ok(Box::<gui::LyfcalApp>::default())),
+        Box::new(|cc| {
+            gui::install_fonts(&cc.egui_ctx);
+            #[allow(unused_mut)]
+            let mut app = gui::LyfcalApp::default();
+            #[cfg(unix)]
+            {
+                if let Some(server) = server {
+                    let ctx = cc.egui_ctx.clone();
+                    server.wake_with(move || ctx.request_repaint());
+                    app.serve(server);
+                }
+                if let Err(error) = app.handle_command(&cc.egui_ctx, command) {
+                    eprintln!("lyfcal: {}", error);
+                }
+            }
+            Ok(Box::new(app))
+        }),
     )
 }
+
+//End this invocation with the outcome of the command the running instance carried out.
+#[cfg(unix)]
+fn exit_forwarded(result: Result<(), LyfcalError>) -> ! {
+    if let Err(error) = result {
+        eprintln!("lyfcal: {}", error);
+        std::process::exit(1);
+    }
+    std::process::exit(0)
+}
+
+fn decode_icon(bytes: &[u8]) -> Result<IconData, LyfcalError> {
+    let icon = image::load_from_memory(bytes)
+        .map_err(|error| LyfcalError::Parse {
+            path: assets::ICON.into(),
+            message: error.to_string(),
+        })?
+        .to_rgba8();
+    let (width, height) = icon.dimensions();
+    Ok(IconData {
+        rgba: icon.into_raw(),
+        width,
+        height,
+    })
+}
@@ -0,0 +1,269 @@
+use chrono::NaiveDate;
+use lyfcal_core::calendar::Calendar;
+use lyfcal_core::clock::Clock;
+use lyfcal_core::config::Config;
+use lyfcal_core::error::LyfcalError;
+use lyfcal_core::status::Status;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const DEFAULT_TEMPLATE: &str = "{glyph} {percent}%";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptOptions {
+    //Print the prompt segment.
+    Print { template: String },
+    //Print the snippet wiring the segment into a shell's prompt.
+    Init(Shell),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Starship,
+}
+
+impl PromptOptions {
+    //Parse the arguments following `lyfcal prompt`.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, LyfcalError> {
+        let mut args = args.into_iter();
+        let mut template = DEFAULT_TEMPLATE.to_string();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "init" => {
+                    let shell = match args.next().as_deref() {
+                        Some("bash") => Shell::Bash,
+                        Some("zsh") => Shell::Zsh,
+                        Some("fish") => Shell::Fish,
+                        Some("starship") => Shell::Starship,
+                        _ => {
+                            return Err(LyfcalError::Usage(
+                                "init takes bash, zsh, fish or starship".to_string(),
+                            ))
+                        }
+                    };
+                    return Ok(Self::Init(shell));
+                }
+                "--template" => {
+                    template = args.next().ok_or_else(|| {
+                        LyfcalError::Usage("--template takes a template".to_string())
+                    })?
+                }
+                _ => {
+                    return Err(LyfcalError::Usage(format!(
+                        "unknown prompt option \"{}\"",
+                        arg
+                    )))
+                }
+            }
+        }
+        Ok(Self::Print { template })
+    }
+}
+
+pub fn run(options: &PromptOptions, clock: &dyn Clock) -> Result<(), LyfcalError> {
+    match options {
+        PromptOptions::Print { template } => {
+            let config_modified = Config::path()
+                .and_then(|path| std::fs::metadata(path).ok())
+                .and_then(|metadata| metadata.modified().ok());
+            let status = cached_status(clock, cache_path().as_deref(), config_modified, || {
+                Ok(Status::new(&Calendar::new(&Config::load(clock)?)?))
+            })?;
+            println!("{}", status.render(template))
+        }
+        PromptOptions::Init(shell) => print!("{}", shell.snippet()),
+    }
+    Ok(())
+}
+
+impl Shell {
+    //Sourced from the shell's startup file, see `lyfcal help`. Sourcing it again leaves the prompt as it is.
+    fn snippet(&self) -> &'static str {
+        match self {
+            Self::Bash => concat!(
+                "# eval \"$(lyfcal prompt init bash)\" in ~/.bashrc\n",
+                "case \"$PS1\" in *'$(lyfcal prompt)'*) ;; *) PS1='$(lyfcal prompt) '\"$PS1\" ;; esac\n",
+            ),
+            Self::Zsh => concat!(
+                "# eval \"$(lyfcal prompt init zsh)\" in ~/.zshrc\n",
+                "setopt PROMPT_SUBST\n",
+                "case \"$PROMPT\" in *'$(lyfcal prompt)'*) ;; *) PROMPT='$(lyfcal prompt) '\"$PROMPT\" ;; esac\n",
+            ),
+            Self::Fish => concat!(
+                "# lyfcal prompt init fish | source in ~/.config/fish/config.fish\n",
+                "functions -q __lyfcal_fish_prompt; or functions -c fish_prompt __lyfcal_fish_prompt\n",
+                "function fish_prompt\n",
+                "    printf '%s ' (lyfcal prompt)\n",
+                "    __lyfcal_fish_prompt\n",
+                "end\n",
+            ),
+            Self::Starship => concat!(
+                "# lyfcal prompt init starship >> ~/.config/starship.toml\n",
+                "[custom.lyfcal]\n",
+                "command = \"lyfcal prompt\"\n",
+                "when = true\n",
+                "format = \"[$output]($style) \"\n",
+            ),
+        }
+    }
+}
+
+//================================================== CACHE ==================================================
+
+//Status computed once per day and config change, since building the day data takes far longer than a prompt can wait.
+#[derive(Debug, Serialize, Deserialize)]
+struct PromptCache {
+    date: NaiveDate,
+    config_modified: Option<SystemTime>,
+    status: Status,
+}
+
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("lyfcal").join("prompt.json"))
+}
+
+//Status cached for the clock's date and the given config modification time, computed and cached again otherwise.
+fn cached_status(
+    clock: &dyn Clock,
+    cache_path: Option<&Path>,
+    config_modified: Option<SystemTime>,
+    compute: impl FnOnce() -> Result<Status, LyfcalError>,
+) -> Result<Status, LyfcalError> {
+    let date = clock.today();
+    let cache = cache_path
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str::<PromptCache>(&text).ok());
+    if let Some(cache) = cache {
+        if cache.date == date && cache.config_modified == config_modified {
+            return Ok(cache.status);
+        }
+    }
+
+    let cache = PromptCache {
+        date,
+        config_modified,
+        status: compute()?,
+    };
+    //A cache that can't be written only costs speed.
+    if let (Some(path), Ok(text)) = (cache_path, serde_json::to_string(&cache)) {
+        let _ = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, text));
+    }
+    Ok(cache.status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+    use lyfcal_core::clock::FixedClock;
+    use lyfcal_core::testing;
+    use std::time::Duration;
+
+    //Noon on the given day of the test clock's month.
+    fn noon(day: u32) -> FixedClock {
+        FixedClock(testing::clock().0 + TimeDelta::days(i64::from(day) - 1))
+    }
+
+    fn status(days_lived: usize) -> Status {
+        Status {
+            days_lived,
+            days_total: 1000,
+            next_milestone: None,
+            events_today: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn cache_holds_until_the_date_or_config_changes() {
+        let path = std::env::temp_dir().join(format!("lyfcal-prompt-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let saved = Some(SystemTime::UNIX_EPOCH);
+        let cached = |clock: &FixedClock, modified, computed: Option<Status>| {
+            cached_status(clock, Some(&path), modified, || {
+                computed.ok_or(LyfcalError::MissingBirthdate)
+            })
+        };
+
+        assert_eq!(cached(&noon(1), saved, Some(status(1))), Ok(status(1)));
+        assert_eq!(cached(&noon(1), saved, None), Ok(status(1)));
+        //Saving the config again.
+        let resaved = saved.map(|time| time + Duration::from_secs(1));
+        assert_eq!(cached(&noon(1), resaved, Some(status(2))), Ok(status(2)));
+        assert_eq!(cached(&noon(1), resaved, None), Ok(status(2)));
+        //The date rolling over.
+        assert_eq!(cached(&noon(2), resaved, Some(status(3))), Ok(status(3)));
+        assert_eq!(cached(&noon(2), resaved, None), Ok(status(3)));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unreadable_cache_is_computed_again() {
+        let path =
+            std::env::temp_dir().join(format!("lyfcal-prompt-bad-{}.json", std::process::id()));
+        std::fs::write(&path, "{").unwrap();
+        let status = cached_status(&noon(1), Some(&path), None, || Ok(status(1)));
+        assert_eq!(status, Ok(self::status(1)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn init_snippets_prepend_once() {
+        for (shell, variable) in [(Shell::Bash, "PS1"), (Shell::Zsh, "PROMPT")] {
+            let snippet = shell.snippet();
+            let guard = format!("case \"${}\" in *'$(lyfcal prompt)'*) ;;", variable);
+            assert!(snippet.contains(&guard), "{:?}", shell);
+        }
+    }
+}
+
+//================================================== BENCHMARKS ==================================================
+
+//Time `lyfcal prompt` spends on the status with and without the cache for a 120 year lifespan, run with:
+//cargo test --release bench_prompt -- --ignored --nocapture
+#[cfg(test)]
+mod benches {
+    use super::*;
+    use lyfcal_core::testing;
+    use std::time::{Duration, Instant};
+
+    const RUNS: u32 = 100;
+
+    fn time_runs(run: impl Fn() -> Status) -> Duration {
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            assert!(run().days_total > 0);
+        }
+        start.elapsed() / RUNS
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_prompt() {
+        let clock = testing::clock();
+        let config = Config {
+            birthdate: NaiveDate::from_ymd_opt(1950, 1, 1),
+            life_expectancy: 120,
+            holiday_region: Some(lyfcal_core::holiday::HolidayRegion::EnglandWales),
+            ..testing::config()
+        };
+        let compute = || Ok(Status::new(&Calendar::new(&config)?));
+        let path =
+            std::env::temp_dir().join(format!("lyfcal-prompt-bench-{}.json", std::process::id()));
+        let computed = time_runs(|| compute().unwrap());
+        //The first run writes the cache the others read.
+        let cached = time_runs(|| cached_status(&clock, Some(&path), None, compute).unwrap());
+        println!(
+            "120 years: computed {:?}, cached {:?} per prompt",
+            computed, cached
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
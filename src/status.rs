@@ -0,0 +1,107 @@
+use lyfcal_core::calendar::Calendar;
+use lyfcal_core::clock::DateClock;
+use lyfcal_core::config::Config;
+use lyfcal_core::error::LyfcalError;
+use lyfcal_core::status::Status;
+use std::io::Write;
+use std::time::Duration;
+
+pub const DEFAULT_TEMPLATE: &str = "{bar} {percent}%";
+
+//Output expected by the status bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFormat {
+    //One line of text, for polybar and i3blocks.
+    Text,
+    //One JSON object per line, for waybar's custom modules.
+    Waybar,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusOptions {
+    pub format: StatusFormat,
+    pub template: String,
+    //Keep running and print again every time the date rolls over.
+    pub watch: bool,
+}
+
+impl StatusOptions {
+    //Parse the arguments following `lyfcal status`.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, LyfcalError> {
+        let mut options = Self {
+            format: StatusFormat::Text,
+            template: DEFAULT_TEMPLATE.to_string(),
+            watch: false,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--format" => {
+                    options.format = match args.next().as_deref() {
+                        Some("text") => StatusFormat::Text,
+                        Some("waybar") => StatusFormat::Waybar,
+                        _ => {
+                            return Err(LyfcalError::Usage(
+                                "--format takes text or waybar".to_string(),
+                            ))
+                        }
+                    }
+                }
+                "--template" => {
+                    options.template = args.next().ok_or_else(|| {
+                        LyfcalError::Usage("--template takes a template".to_string())
+                    })?
+                }
+                "--watch" => options.watch = true,
+                _ => {
+                    return Err(LyfcalError::Usage(format!(
+                        "unknown status option \"{}\"",
+                        arg
+                    )))
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+//How soon --watch tries again after the config couldn't be read, rather than waiting for the next rollover.
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+//Print the status, then with --watch again after every rollover, re-reading the config each time.
+pub fn run(options: &StatusOptions) -> Result<(), LyfcalError> {
+    let mut clock = DateClock::default();
+    loop {
+        let line = match status_line(options, &clock) {
+            Ok(line) => line,
+            //A config caught mid edit mustn't end the watch, the bar keeps its last line until the retry.
+            Err(error) if options.watch => {
+                eprintln!("lyfcal: {}", error);
+                std::thread::sleep(clock.time_until_rollover().min(RETRY_INTERVAL));
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        //The status bar reads line by line, so each line is flushed straight away.
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{}", line)
+            .and_then(|_| stdout.flush())
+            .map_err(|error| LyfcalError::Terminal(error.to_string()))?;
+
+        if !options.watch {
+            return Ok(());
+        }
+        while clock.poll().is_none() {
+            std::thread::sleep(clock.time_until_rollover());
+        }
+    }
+}
+
+fn status_line(options: &StatusOptions, clock: &DateClock) -> Result<String, LyfcalError> {
+    let config = Config::load(clock.source())?;
+    let status = Status::new(&Calendar::new(&config)?);
+    Ok(match options.format {
+        StatusFormat::Text => status.render(&options.template),
+        StatusFormat::Waybar => status.waybar_json(&options.template),
+    })
+}
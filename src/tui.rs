@@ -0,0 +1,448 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{
+    Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::{cursor, queue, terminal};
+use lyfcal_core::calendar::Calendar;
+use lyfcal_core::clock::DateClock;
+use lyfcal_core::color::Color as UnitColor;
+use lyfcal_core::config::Config;
+use lyfcal_core::error::LyfcalError;
+use lyfcal_core::status::Status;
+use std::io::{Stdout, Write};
+
+const STATUS_TEMPLATE: &str =
+    "{bar} {percent}% · {lived} days lived · {remaining} to go · {milestone}";
+
+//Period of days drawn as a single terminal pixel, each pixel being half a character cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Zoom {
+    Day,
+    Week,
+    Month,
+}
+
+impl Zoom {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    fn zoom_in(&self) -> Self {
+        match self {
+            Self::Day | Self::Week => Self::Day,
+            Self::Month => Self::Week,
+        }
+    }
+
+    fn zoom_out(&self) -> Self {
+        match self {
+            Self::Day => Self::Week,
+            Self::Week | Self::Month => Self::Month,
+        }
+    }
+
+    //First day of the period holding a date, the days sharing it being drawn as one pixel.
+    fn period_start(&self, date: NaiveDate, week_start: Weekday) -> NaiveDate {
+        match self {
+            Self::Day => date,
+            Self::Week => date.week(week_start).first_day(),
+            Self::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+//Show the life calendar in the terminal until quit, following the date across midnight.
+pub fn run() -> Result<(), LyfcalError> {
+    let mut app = TuiApp::new()?;
+    let mut terminal =
+        Terminal::enter().map_err(|error| LyfcalError::Terminal(error.to_string()))?;
+    app.run(&mut terminal.stdout)
+        .map_err(|error| LyfcalError::Terminal(error.to_string()))
+}
+
+//Raw mode on the alternate screen, restored however the TUI exits.
+struct Terminal {
+    stdout: Stdout,
+}
+
+impl Terminal {
+    fn enter() -> std::io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = std::io::stdout();
+        queue!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        stdout.flush()?;
+        Ok(Self { stdout })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = queue!(
+            self.stdout,
+            ResetColor,
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = self.stdout.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct TuiApp {
+    clock: DateClock,
+    calendar: Calendar,
+    zoom: Zoom,
+    //First terminal row of the canvas shown.
+    scroll: usize,
+    canvas: Canvas,
+}
+
+impl TuiApp {
+    fn new() -> Result<Self, LyfcalError> {
+        let clock = DateClock::default();
+        let calendar = Calendar::new(&Config::load(clock.source())?)?;
+        Ok(Self {
+            clock,
+            calendar,
+            zoom: Zoom::Week,
+            scroll: 0,
+            canvas: Canvas::default(),
+        })
+    }
+
+    fn run(&mut self, stdout: &mut Stdout) -> std::io::Result<()> {
+        self.relayout(true)?;
+        loop {
+            self.draw(stdout)?;
+            //Wake at midnight even without input so the elapsed date moves on.
+            if event::poll(self.clock.time_until_rollover())? {
+                match event::read()? {
+                    Event::Key(key) if key.kind != KeyEventKind::Release => {
+                        let page = self.view_height()?.max(1);
+                        match key.code {
+                            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                self.scroll = self.scroll.saturating_sub(1)
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => self.scroll += 1,
+                            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(page),
+                            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll += page,
+                            KeyCode::Home | KeyCode::Char('g') => self.scroll = 0,
+                            KeyCode::End | KeyCode::Char('G') => self.scroll = usize::MAX,
+                            KeyCode::Char('t') => self.relayout(true)?,
+                            KeyCode::Char('d') => self.set_zoom(Zoom::Day)?,
+                            KeyCode::Char('w') => self.set_zoom(Zoom::Week)?,
+                            KeyCode::Char('m') => self.set_zoom(Zoom::Month)?,
+                            KeyCode::Char('+') | KeyCode::Char('=') => {
+                                self.set_zoom(self.zoom.zoom_in())?
+                            }
+                            KeyCode::Char('-') => self.set_zoom(self.zoom.zoom_out())?,
+                            _ => {}
+                        }
+                    }
+                    Event::Resize(..) => self.relayout(false)?,
+                    _ => {}
+                }
+            }
+            if self.clock.poll().is_some() {
+                //Pick up config changes made in the app too.
+                //A config that can't be read or has problems leaves the current one in place.
+                let mut config = Config::load(self.clock.source())
+                    .ok()
+                    .filter(|config| config.validate().is_ok())
+                    .unwrap_or_else(|| self.calendar.config.clone());
+                if config.elapsed_date_bool {
+                    config.elapsed_date = self.clock.today();
+                }
+                let _ = self.calendar.update_config(&config);
+                self.relayout(false)?;
+            }
+        }
+    }
+
+    fn set_zoom(&mut self, zoom: Zoom) -> std::io::Result<()> {
+        if zoom != self.zoom {
+            self.zoom = zoom;
+            self.relayout(true)?;
+        }
+        Ok(())
+    }
+
+    //Rows left for the grid above the status line.
+    fn view_height(&self) -> std::io::Result<usize> {
+        let (_, rows) = terminal::size()?;
+        Ok((rows as usize).saturating_sub(1))
+    }
+
+    //Lay the canvas out for the terminal width, optionally scrolling the elapsed date into view.
+    fn relayout(&mut self, scroll_to_today: bool) -> std::io::Result<()> {
+        let (cols, _) = terminal::size()?;
+        self.canvas = Canvas::new(&self.calendar, self.zoom, cols as usize);
+        if scroll_to_today {
+            let today_row = self.canvas.today_row / 2;
+            self.scroll = today_row.saturating_sub(self.view_height()? / 2);
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, stdout: &mut Stdout) -> std::io::Result<()> {
+        let (cols, rows) = terminal::size()?;
+        let view_height = self.view_height()?;
+        let canvas_rows = self.canvas.height.div_ceil(2);
+        self.scroll = self.scroll.min(canvas_rows.saturating_sub(view_height));
+
+        queue!(stdout, cursor::MoveTo(0, 0))?;
+        for row in 0..view_height {
+            let y = (self.scroll + row) * 2;
+            //Colours are only sent when they change, which keeps frames small over SSH.
+            let mut current = (None, None);
+            for x in 0..cols as usize {
+                //Half blocks give each character cell two pixels, the top one in the foreground.
+                let (glyph, foreground, background) =
+                    match (self.canvas.pixel(x, y), self.canvas.pixel(x, y + 1)) {
+                        (Some(top), Some(bottom)) => ('▀', rgb(top), rgb(bottom)),
+                        (Some(top), None) => ('▀', rgb(top), Color::Reset),
+                        (None, Some(bottom)) => ('▄', rgb(bottom), Color::Reset),
+                        (None, None) => (' ', current.0.unwrap_or(Color::Reset), Color::Reset),
+                    };
+                if current.0 != Some(foreground) {
+                    queue!(stdout, SetForegroundColor(foreground))?;
+                }
+                if current.1 != Some(background) {
+                    queue!(stdout, SetBackgroundColor(background))?;
+                }
+                current = (Some(foreground), Some(background));
+                queue!(stdout, Print(glyph))?;
+            }
+            queue!(stdout, ResetColor, cursor::MoveToNextLine(1))?;
+        }
+
+        let status = Status::new(&self.calendar).render(STATUS_TEMPLATE);
+        let keys = format!(
+            "[{}] d/w/m zoom  ↑↓ scroll  t today  q quit",
+            self.zoom.label()
+        );
+        let line = format!(" {}  {}", status, keys);
+        let line: String = line
+            .chars()
+            .chain(std::iter::repeat(' '))
+            .take(cols as usize)
+            .collect();
+        queue!(
+            stdout,
+            cursor::MoveTo(0, rows.saturating_sub(1)),
+            SetAttribute(Attribute::Reverse),
+            Print(line),
+            SetAttribute(Attribute::Reset)
+        )?;
+        stdout.flush()
+    }
+}
+
+fn rgb(color: UnitColor) -> Color {
+    Color::Rgb {
+        r: color.r(),
+        g: color.g(),
+        b: color.b(),
+    }
+}
+
+//================================================== CANVAS ==================================================
+
+//Pixels of the whole lifespan laid out like the grid window across the terminal width, growing downwards.
+#[derive(Debug, Default)]
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<Option<UnitColor>>,
+    //Pixel row holding the elapsed date.
+    today_row: usize,
+}
+
+impl Canvas {
+    fn new(calendar: &Calendar, zoom: Zoom, width: usize) -> Self {
+        let pixels = match zoom {
+            Zoom::Day => day_pixels(calendar, width),
+            Zoom::Week | Zoom::Month => period_pixels(calendar, zoom, width),
+        };
+        let height = pixels.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+        let today = calendar.elapsed_date();
+
+        //Average the colour of every day falling in the same pixel.
+        let mut sums = vec![(0u32, [0u32; 3], false); width * height];
+        let mut today_row = 0;
+        for (date, &(x, y)) in calendar.events.keys().zip(&pixels) {
+            //Only a terminal too narrow for a single week strip cuts the grid off.
+            if x >= width {
+                continue;
+            }
+            let color = day_color(calendar, *date);
+            let sum = &mut sums[y * width + x];
+            sum.0 += 1;
+            for (channel, value) in sum.1.iter_mut().zip([color.r(), color.g(), color.b()]) {
+                *channel += value as u32;
+            }
+            if *date == today {
+                sum.2 = true;
+                today_row = y;
+            }
+        }
+        let pixels = sums
+            .iter()
+            .map(|(count, channels, is_today)| {
+                if *count == 0 {
+                    return None;
+                }
+                let [r, g, b] = channels.map(|channel| (channel / count) as u8);
+                let color = UnitColor::from_rgb(r, g, b);
+                if *is_today {
+                    Some(calendar.config.color_today.over(color))
+                } else {
+                    Some(color)
+                }
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+            today_row,
+        }
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> Option<UnitColor> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.pixels[y * self.width + x]
+    }
+}
+
+//Pixel of each day in date order, one per day laid out like the grid window.
+fn day_pixels(calendar: &Calendar, width: usize) -> Vec<(usize, usize)> {
+    calendar
+        .scrolling_layout(width as f32, 1.0)
+        .units
+        .iter()
+        .map(|unit| {
+            let center = unit.center();
+            (center.x.max(0.0) as usize, center.y.max(0.0) as usize)
+        })
+        .collect()
+}
+
+//Pixel of each day in date order, one per week or month, in rows of whole years where the width allows.
+fn period_pixels(calendar: &Calendar, zoom: Zoom, width: usize) -> Vec<(usize, usize)> {
+    let per_year = match zoom {
+        Zoom::Day => 365,
+        Zoom::Week => 52,
+        Zoom::Month => 12,
+    };
+    let per_row = if width >= per_year {
+        width / per_year * per_year
+    } else {
+        width.max(1)
+    };
+    let mut index = 0;
+    let mut current = None;
+    calendar
+        .events
+        .keys()
+        .map(|date| {
+            let start = zoom.period_start(*date, calendar.config.week_start);
+            if current.is_some_and(|current| current != start) {
+                index += 1;
+            }
+            current = Some(start);
+            (index % per_row, index / per_row)
+        })
+        .collect()
+}
+
+//Opaque colour of a day, its unit fills composed over the theme's background like the wallpaper draws them.
+fn day_color(calendar: &Calendar, date: NaiveDate) -> UnitColor {
+    calendar
+        .unit_fills(date)
+        .fold(calendar.config.window_visuals.background, |color, fill| {
+            fill.over(color)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lyfcal_core::testing;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    //80 years from a Monday, with the elapsed date on the 1st of June 2024.
+    fn calendar() -> Calendar {
+        Calendar::new(&Config {
+            birthdate: Some(date(2001, 1, 1)),
+            ..testing::config()
+        })
+        .unwrap()
+    }
+
+    fn drawn_pixels(canvas: &Canvas) -> usize {
+        canvas.pixels.iter().flatten().count()
+    }
+
+    #[test]
+    fn each_period_is_one_pixel() {
+        let calendar = calendar();
+        let days = calendar.events.len();
+        assert_eq!(drawn_pixels(&Canvas::new(&calendar, Zoom::Day, 120)), days);
+        assert_eq!(
+            drawn_pixels(&Canvas::new(&calendar, Zoom::Week, 120)),
+            days.div_ceil(7)
+        );
+        //The lifespan ends on the first day of a month.
+        assert_eq!(
+            drawn_pixels(&Canvas::new(&calendar, Zoom::Month, 120)),
+            80 * 12 + 1
+        );
+    }
+
+    #[test]
+    fn periods_fill_rows_of_whole_years() {
+        let calendar = calendar();
+        //Ten years of months per row, June 2024 in the third.
+        let months = Canvas::new(&calendar, Zoom::Month, 125);
+        assert_eq!(months.width, 125);
+        assert_eq!(months.today_row, 2);
+        assert!(months.pixel(120, 0).is_none());
+        //Two years of weeks per row, the week of June 1st 2024 being week 1220 of the lifespan.
+        let weeks = Canvas::new(&calendar, Zoom::Week, 110);
+        assert_eq!(weeks.today_row, 1220 / 104);
+        //Too narrow for a year, periods wrap at the width.
+        assert_eq!(Canvas::new(&calendar, Zoom::Month, 10).today_row, 281 / 10);
+    }
+
+    #[test]
+    fn pixel_is_the_average_of_its_days() {
+        let calendar = calendar();
+        let canvas = Canvas::new(&calendar, Zoom::Month, 120);
+        //January 2010, with the birthday and weekends in it.
+        let days: Vec<_> = (1..=31)
+            .map(|day| day_color(&calendar, date(2010, 1, day)))
+            .collect();
+        let average = |channel: fn(&UnitColor) -> u8| {
+            (days.iter().map(|color| channel(color) as u32).sum::<u32>() / 31) as u8
+        };
+        let expected = UnitColor::from_rgb(
+            average(UnitColor::r),
+            average(UnitColor::g),
+            average(UnitColor::b),
+        );
+        assert_ne!(days[0], days[5]);
+        assert_eq!(canvas.pixel(108, 0), Some(expected));
+    }
+}
@@ -0,0 +1,360 @@
+use eframe::egui::*;
+use image::{imageops::FilterType, Rgba, RgbaImage};
+use lyfcal_core::config::Config;
+use lyfcal_core::error::LyfcalError;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::draw::DrawData;
+
+//Screen the wallpaper is rendered for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Screen {
+    //Size in points, as reported for the monitor.
+    pub size: Vec2,
+    pub pixels_per_point: f32,
+}
+
+impl Screen {
+    pub fn pixel_size(&self) -> [u32; 2] {
+        [
+            (self.size.x * self.pixels_per_point).round() as u32,
+            (self.size.y * self.pixels_per_point).round() as u32,
+        ]
+    }
+}
+
+//Render the wallpaper, write it to the configured path and run the hook command on it.
+pub fn generate(config: &Config, screen: Screen) -> Result<PathBuf, LyfcalError> {
+    let path = PathBuf::from(config.wallpaper_path.trim());
+    if path.as_os_str().is_empty() {
+        return Err(LyfcalError::NoWallpaperPath);
+    }
+    let background = Some(config.wallpaper_background.trim())
+        .filter(|background| !background.is_empty())
+        .map(Path::new);
+
+    let wallpaper = render(config, screen, background)?;
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).map_err(|error| LyfcalError::Io {
+            action: "create",
+            path: parent.to_path_buf(),
+            message: error.to_string(),
+        })?;
+    }
+    wallpaper.save(&path).map_err(|error| LyfcalError::Io {
+        action: "write",
+        path: path.clone(),
+        message: error.to_string(),
+    })?;
+
+    let command = config.wallpaper_command.trim();
+    if !command.is_empty() {
+        run_hook(command, &path)?;
+    }
+    Ok(path)
+}
+
+//Draw the life grid with the same code as the life viewport, then rasterize it over the background.
+pub fn render(
+    config: &Config,
+    screen: Screen,
+    background: Option<&Path>,
+) -> Result<RgbaImage, LyfcalError> {
+    let [width, height] = screen.pixel_size();
+    if width == 0 || height == 0 {
+        return Err(LyfcalError::UnknownScreenSize);
+    }
+    let mut image = match background {
+        Some(path) => image::open(path)
+            .map_err(|error| LyfcalError::Io {
+                action: "open",
+                path: path.to_path_buf(),
+                message: error.to_string(),
+            })?
+            .resize_to_fill(width, height, FilterType::Triangle)
+            .to_rgba8(),
+        //Without an image the grid sits on the theme's window background, as in the life viewport.
+        None => RgbaImage::from_pixel(
+            width,
+            height,
+            Rgba(config.window_visuals.background.to_array()),
+        ),
+    };
+
+    let mut draw_data = DrawData::default();
+    draw_data.update_config(config)?;
+    let ctx = Context::default();
+    let mut raw_input = RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, screen.size)),
+        ..Default::default()
+    };
+    raw_input
+        .viewports
+        .entry(ViewportId::ROOT)
+        .or_default()
+        .native_pixels_per_point = Some(screen.pixels_per_point);
+    let output = ctx.run(raw_input, |ctx| {
+        CentralPanel::default()
+            .frame(Frame::none())
+            .show(ctx, |ui| draw_data.draw_lyfcal(ui));
+    });
+    for primitive in ctx.tessellate(output.shapes, output.pixels_per_point) {
+        if let epaint::Primitive::Mesh(mesh) = &primitive.primitive {
+            let clip_rect = Rect::from_min_max(
+                (primitive.clip_rect.min.to_vec2() * output.pixels_per_point).to_pos2(),
+                (primitive.clip_rect.max.to_vec2() * output.pixels_per_point).to_pos2(),
+            );
+            rasterize_mesh(&mut image, mesh, output.pixels_per_point, clip_rect);
+        }
+    }
+    Ok(image)
+}
+
+//Run the hook command through the shell with the wallpaper path in place of {path}, or appended when there is none.
+//The command isn't waited on since some setters, like swaybg, keep running to hold the wallpaper.
+fn run_hook(command: &str, path: &Path) -> Result<(), LyfcalError> {
+    let path = shell_quote(&path.to_string_lossy());
+    let command = if command.contains("{path}") {
+        command.replace("{path}", &path)
+    } else {
+        format!("{} {}", command, path)
+    };
+    let mut child = if cfg!(windows) {
+        Command::new("cmd").args(["/C", &command]).spawn()
+    } else {
+        Command::new("sh").args(["-c", &command]).spawn()
+    }
+    .map_err(|error| LyfcalError::Hook {
+        command: command.clone(),
+        message: error.to_string(),
+    })?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+fn shell_quote(text: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", text)
+    } else {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    }
+}
+
+//================================================== RASTERIZER ==================================================
+
+//Fill a mesh's triangles with their interpolated vertex colours, blended the way egui blends premultiplied colours.
+//The grid is untextured, so vertex uvs are ignored.
+fn rasterize_mesh(image: &mut RgbaImage, mesh: &Mesh, pixels_per_point: f32, clip_rect: Rect) {
+    let bounds = clip_rect.intersect(Rect::from_min_size(
+        Pos2::ZERO,
+        vec2(image.width() as f32, image.height() as f32),
+    ));
+    if !bounds.is_positive() {
+        return;
+    }
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+        let [pa, pb, pc] = [a, b, c].map(|vertex| vertex.pos.to_vec2() * pixels_per_point);
+        let area = edge(pa, pb, pc);
+        if area == 0.0 {
+            continue;
+        }
+        let [ca, cb, cc] = [a, b, c].map(|vertex| Rgba32::from(vertex.color));
+
+        let triangle_bounds =
+            Rect::from_points(&[pa.to_pos2(), pb.to_pos2(), pc.to_pos2()]).intersect(bounds);
+        let (x_min, x_max) = (
+            triangle_bounds.min.x.floor() as u32,
+            triangle_bounds.max.x.ceil() as u32,
+        );
+        let (y_min, y_max) = (
+            triangle_bounds.min.y.floor() as u32,
+            triangle_bounds.max.y.ceil() as u32,
+        );
+        for y in y_min..y_max.min(image.height()) {
+            for x in x_min..x_max.min(image.width()) {
+                //Sample at the pixel centre, in either winding order.
+                let p = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                let (wa, wb, wc) = (
+                    edge(pb, pc, p) / area,
+                    edge(pc, pa, p) / area,
+                    edge(pa, pb, p) / area,
+                );
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                let color = ca * wa + cb * wb + cc * wc;
+                blend(image.get_pixel_mut(x, y), color);
+            }
+        }
+    }
+}
+
+fn edge(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+fn blend(pixel: &mut Rgba<u8>, color: Rgba32) {
+    let inverse_alpha = 1.0 - color.0[3] / 255.0;
+    for channel in 0..4 {
+        let blended = color.0[channel] + pixel.0[channel] as f32 * inverse_alpha;
+        pixel.0[channel] = blended.round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+//Premultiplied gamma space colour with float channels for interpolation.
+#[derive(Debug, Clone, Copy)]
+struct Rgba32([f32; 4]);
+
+impl From<Color32> for Rgba32 {
+    fn from(color: Color32) -> Self {
+        Self(color.to_array().map(f32::from))
+    }
+}
+
+impl std::ops::Mul<f32> for Rgba32 {
+    type Output = Self;
+
+    fn mul(self, factor: f32) -> Self {
+        Self(self.0.map(|channel| channel * factor))
+    }
+}
+
+impl std::ops::Add for Rgba32 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self([0, 1, 2, 3].map(|i| self.0[i] + other.0[i]))
+    }
+}
+
+//================================================== GOLDEN IMAGES ==================================================
+
+//Rendered grids compared against PNG snapshots in tests/golden.
+//After an intended change to the drawing, write new snapshots with:
+//LYFCAL_UPDATE_GOLDEN=1 cargo test golden
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use lyfcal_core::config::{FlowMode, HorizontalFlow, VerticalFlow, WeekOrientation};
+    use lyfcal_core::holiday::HolidayRegion;
+    use lyfcal_core::testing;
+
+    const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+    //Per channel difference allowed, for float rounding that differs between platforms.
+    const TOLERANCE: u8 = 2;
+
+    fn config() -> Config {
+        let mut config = Config {
+            birthdate: NaiveDate::from_ymd_opt(1990, 3, 15),
+            holiday_region: Some(HolidayRegion::EnglandWales),
+            ..testing::config()
+        };
+        config.notes.insert(
+            NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+            "note".to_string(),
+        );
+        config
+    }
+
+    fn screen(width: f32, height: f32, pixels_per_point: f32) -> Screen {
+        Screen {
+            size: vec2(width, height),
+            pixels_per_point,
+        }
+    }
+
+    fn assert_golden(name: &str, image: &RgbaImage) {
+        let path = Path::new(GOLDEN_DIR).join(format!("{}.png", name));
+        if std::env::var_os("LYFCAL_UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+            image.save(&path).unwrap();
+            return;
+        }
+        let golden = image::open(&path)
+            .unwrap_or_else(|error| {
+                panic!(
+                    "{}: {}, create it with LYFCAL_UPDATE_GOLDEN=1",
+                    path.display(),
+                    error
+                )
+            })
+            .to_rgba8();
+        assert_eq!(golden.dimensions(), image.dimensions(), "size of {}", name);
+        let differing = golden
+            .pixels()
+            .zip(image.pixels())
+            .filter(|(expected, actual)| {
+                expected
+                    .0
+                    .iter()
+                    .zip(actual.0)
+                    .any(|(expected, actual)| expected.abs_diff(actual) > TOLERANCE)
+            })
+            .count();
+        if differing > 0 {
+            let actual_path = std::env::temp_dir().join(format!("lyfcal-{}.actual.png", name));
+            image.save(&actual_path).unwrap();
+            panic!(
+                "{} pixels differ from {}, the rendered image is at {}",
+                differing,
+                path.display(),
+                actual_path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn golden_linear() {
+        let image = render(&config(), screen(320.0, 200.0, 1.0), None).unwrap();
+        assert_golden("linear", &image);
+    }
+
+    #[test]
+    fn golden_snake_vertical_right_to_left() {
+        let config = Config {
+            week_orientation: WeekOrientation::Vertical,
+            horizontal_flow: HorizontalFlow::RightToLeft,
+            flow_mode: FlowMode::Snake,
+            ..config()
+        };
+        let image = render(&config, screen(200.0, 320.0, 1.0), None).unwrap();
+        assert_golden("snake_vertical_right_to_left", &image);
+    }
+
+    #[test]
+    fn golden_spiral_bottom_up() {
+        let config = Config {
+            vertical_flow: VerticalFlow::BottomUp,
+            flow_mode: FlowMode::Spiral,
+            col_spacing: 0.5,
+            row_spacing: 0.5,
+            ..config()
+        };
+        let image = render(&config, screen(240.0, 240.0, 1.0), None).unwrap();
+        assert_golden("spiral_bottom_up", &image);
+    }
+
+    #[test]
+    fn golden_high_dpi() {
+        let image = render(&config(), screen(160.0, 100.0, 2.0), None).unwrap();
+        assert_eq!(image.dimensions(), (320, 200));
+        assert_golden("high_dpi", &image);
+    }
+
+    #[test]
+    fn theme_background_fills_around_the_grid() {
+        let mut config = config();
+        lyfcal_core::theme::Theme::light().apply(&mut config);
+        let image = render(&config, screen(160.0, 100.0, 1.0), None).unwrap();
+        let background = config.window_visuals.background.to_array();
+        assert_eq!(image.get_pixel(0, 0).0, background);
+        assert_eq!(image.get_pixel(159, 99).0, background);
+    }
+}